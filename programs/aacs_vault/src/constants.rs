@@ -3,4 +3,41 @@ use anchor_lang::prelude::*;
 #[constant]
 pub const VAULT_SIGNER_PREFIX: &[u8; 17] = b"aacs_vault_signer";
 
+#[constant]
+pub const SUB_ACCOUNT_PREFIX: &[u8; 22] = b"aacs_vault_sub_account";
+
+#[constant]
+pub const EPHEMERAL_SIGNER_PREFIX: &[u8; 21] = b"aacs_ephemeral_signer";
+
+#[constant]
+pub const PROPOSAL_ACTIONS_PREFIX: &[u8; 21] = b"aacs_proposal_actions";
+
+#[constant]
+pub const EXECUTION_RESULT_PREFIX: &[u8; 26] = b"aacs_proposal_exec_results";
+
+#[constant]
+pub const STREAM_PREFIX: &[u8; 11] = b"aacs_stream";
+
+#[constant]
+pub const PROGRAM_ALLOWLIST_PREFIX: &[u8; 22] = b"aacs_program_allowlist";
+
+#[constant]
+pub const DIRECT_EXECUTION_RECORD_PREFIX: &[u8; 21] = b"aacs_direct_execution";
+
 pub const DEFAULT_FLOW_EXPIRY_DURATION: i64 = 30 * 24 * 60 * 60; // 1 month
+
+pub const DEFAULT_MAX_NAME_LEN: u16 = 64;
+pub const DEFAULT_MAX_ACTIONS: u16 = 64;
+pub const DEFAULT_MAX_ACTION_DATA_LEN: u32 = 1232; // Transaction packet size
+pub const DEFAULT_MAX_ACCOUNT_SPECS: u16 = 64;
+
+pub const MAX_GUARDS: usize = 16;
+pub const MAX_DEPENDENCIES: usize = 8;
+pub const MAX_TOKEN_OUTFLOW_CAPS: usize = 8;
+/// Basis points of a placeholder taking the whole source amount.
+pub const MAX_BASIS_POINTS: u16 = 10_000;
+
+pub const ADDRESS_LOOKUP_TABLE_PROGRAM_ID: Pubkey =
+    pubkey!("AddressLookupTab1e1111111111111111111111111");
+/// Size of the metadata preceding the addresses of an address lookup table account.
+pub const LOOKUP_TABLE_META_SIZE: usize = 56;
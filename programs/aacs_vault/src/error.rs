@@ -20,4 +20,110 @@ pub enum ErrorCode {
     InvalidVaultSigner,
     #[msg("Not enough tokens for lamports")]
     NotEnoughTokens,
+    #[msg("Stream schedule must satisfy start <= cliff <= end with start < end")]
+    InvalidStreamSchedule,
+    #[msg("Stream amount must be greater than zero")]
+    InvalidStreamAmount,
+    #[msg("Nothing is available to withdraw from the stream")]
+    NothingToWithdraw,
+    #[msg("Token accounts do not match the stream mint")]
+    StreamMintMismatch,
+    #[msg("Vault has an allowlist enabled but it was not provided")]
+    ProgramAllowlistRequired,
+    #[msg("Action targets a program that is not on the vault allowlist")]
+    ProgramNotAllowed,
+    #[msg("Action instruction does not match any allowlisted discriminator of its program")]
+    InstructionNotAllowed,
+    #[msg("Action must not target the vault program itself")]
+    SelfReferentialAction,
+    #[msg("Action must not pass the vault account as writable")]
+    WritableVaultInAction,
+    #[msg("Action must not pass the proposal account as writable")]
+    WritableProposalInAction,
+    #[msg("Vault is already executing actions")]
+    ReentrantExecution,
+    #[msg("Account index is outside the account key table")]
+    InvalidAccountIndex,
+    #[msg("Account key table must not contain duplicate keys")]
+    DuplicateAccountKey,
+    #[msg("Account key table can hold at most 256 keys")]
+    TooManyAccountKeys,
+    #[msg("Remaining accounts do not match the account key table")]
+    AccountKeyMismatch,
+    #[msg("Proposal actions account does not belong to the proposal or is out of order")]
+    InvalidProposalActions,
+    #[msg("Proposal actions do not match the proposal's actions hash")]
+    ActionsHashMismatch,
+    #[msg("Proposal holds too many actions")]
+    TooManyActions,
+    #[msg("Proposal has no actions to remove")]
+    NoActionsToRemove,
+    #[msg("Proposal name exceeds the vault's maximum name length")]
+    NameTooLong,
+    #[msg("Action data exceeds the vault's maximum action data length")]
+    ActionDataTooLarge,
+    #[msg("Action has more account specs than the vault allows")]
+    TooManyAccountSpecs,
+    #[msg("Action requires a signer that did not sign the transaction")]
+    MissingActionSigner,
+    #[msg("Proposal co-signer did not sign the transaction")]
+    MissingCoSigner,
+    #[msg("Action failed")]
+    ActionFailed,
+    #[msg("Actions of a group must be consecutive")]
+    NonContiguousActionGroup,
+    #[msg("Instruction does not apply to this kind of proposal")]
+    WrongProposalKind,
+    #[msg("Action data buffer is invalid or not attached to the proposal")]
+    InvalidActionDataBuffer,
+    #[msg("Action data buffer is attached to a proposal and can no longer be written")]
+    ActionDataBufferFrozen,
+    #[msg("Write exceeds the length of the action data buffer")]
+    ActionDataBufferOverflow,
+    #[msg("Action data buffer does not match the expected length or hash")]
+    ActionDataBufferMismatch,
+    #[msg("Action data buffer is attached to a proposal that can still execute")]
+    ActionDataBufferInUse,
+    #[msg("Transaction message is malformed")]
+    InvalidTransactionMessage,
+    #[msg("Only legacy and v0 transaction messages are supported")]
+    UnsupportedMessageVersion,
+    #[msg("Address lookup table is missing, invalid or lacks a referenced address")]
+    InvalidAddressLookupTable,
+    #[msg("Proposal guard was violated")]
+    GuardViolated,
+    #[msg("Account checked by a proposal guard was not passed")]
+    MissingGuardAccount,
+    #[msg("Guard condition does not apply to the account or to guards checked before execution")]
+    InvalidGuard,
+    #[msg("Proposal has more guards than allowed")]
+    TooManyGuards,
+    #[msg("Execution moved more out of the vault than its outflow cap allows")]
+    OutflowCapExceeded,
+    #[msg("Outflow cap lists too many mints")]
+    TooManyTokenOutflowCaps,
+    #[msg("Action placeholder is out of range or its source is not available")]
+    InvalidPlaceholder,
+    #[msg("Template account key cannot be resolved for this vault and proposal")]
+    InvalidTemplateKey,
+    #[msg("Proposal dependency is not completed with all of its actions executed")]
+    DependencyNotCompleted,
+    #[msg("Proposal dependency account was not passed")]
+    MissingDependencyAccount,
+    #[msg("Proposal dependency is not a proposal of the same vault, or is the proposal itself")]
+    InvalidDependency,
+    #[msg("Proposal has more dependencies than allowed")]
+    TooManyDependencies,
+    #[msg("Proposal batch is empty or its accounts do not match the proposals")]
+    InvalidProposalBatch,
+    #[msg("Direct execution nonce does not match the vault's next nonce")]
+    InvalidDirectExecutionNonce,
+    #[msg("Direct execution is disabled for this vault")]
+    DirectExecutionDisabled,
+    #[msg("Sub-account is not opened, or its account was not passed")]
+    SubAccountNotOpened,
+    #[msg("Committed actions do not have the size the proposal committed to")]
+    CommittedActionsSizeMismatch,
+    #[msg("Action group id is already used by another chunk of the proposal")]
+    ActionGroupSpansChunks,
 }
@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
 
-use crate::state::Action;
+use crate::state::{
+    Action, ActionOutcome, ActionResult, AllowedProgram, Guard, OutflowCap, PlaceholderSubstitution,
+    ProposalLimits,
+};
 
 #[event]
 pub struct VaultCreated {
@@ -9,6 +12,14 @@ pub struct VaultCreated {
     pub bump: u8,
 }
 
+#[event]
+pub struct SubAccountOpened {
+    pub vault: Pubkey,
+    pub sub_account: Pubkey,
+    pub vault_signer: Pubkey,
+    pub index: u8,
+}
+
 #[event]
 pub struct SolDeposited {
     pub vault: Pubkey,
@@ -50,25 +61,177 @@ pub struct ProposalCreated {
     pub vault: Pubkey,
     pub name: String,
     pub created_date: i64,
+    pub ephemeral_signers: Vec<Pubkey>,
+    pub co_signers: Vec<Pubkey>,
+    pub account_keys: Vec<Pubkey>,
     pub actions: Vec<Action>,
 }
 
+#[event]
+pub struct CommittedProposalCreated {
+    pub proposal: Pubkey,
+    pub vault: Pubkey,
+    pub name: String,
+    pub created_date: i64,
+    pub ephemeral_signers: Vec<Pubkey>,
+    pub co_signers: Vec<Pubkey>,
+    pub action_count: u16,
+    pub actions_hash: [u8; 32],
+}
+
+#[event]
+pub struct ActionDataBufferCreated {
+    pub buffer: Pubkey,
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub data_len: u32,
+}
+
+#[event]
+pub struct ActionDataBufferAttached {
+    pub buffer: Pubkey,
+    pub proposal: Pubkey,
+    pub data_len: u32,
+    pub data_hash: [u8; 32],
+}
+
 #[event]
 pub struct ActionsAppended {
     pub proposal: Pubkey,
+    pub chunk_index: u16,
+    pub account_keys: Vec<Pubkey>,
     pub actions: Vec<Action>,
 }
 
+#[event]
+pub struct ActionsRemoved {
+    pub proposal: Pubkey,
+    pub chunk_index: u16,
+    pub action_count: u16,
+}
+
+#[event]
+pub struct ProposalActionFailed {
+    pub vault: Pubkey,
+    /// `None` for direct executions.
+    pub proposal: Option<Pubkey>,
+    pub action_index: u16,
+    /// `None` when the action's program index is outside its key table.
+    pub program_id: Option<Pubkey>,
+    pub error_code: u64,
+}
+
 #[event]
 pub struct ProposalExecuted {
     pub vault: Pubkey,
     pub proposal: Pubkey,
     pub executed_date: i64,
+    pub outcomes: Vec<ActionOutcome>,
+    pub results: Vec<ActionResult>,
+    pub substitutions: Vec<PlaceholderSubstitution>,
 }
 
 #[event]
 pub struct ProposalDirectExecuted {
     pub vault: Pubkey,
     pub created_date: i64,
+    pub nonce: u64,
+    pub record: Pubkey,
+    pub actions_hash: [u8; 32],
+    pub account_keys: Vec<Pubkey>,
     pub actions: Vec<Action>,
+    pub outcomes: Vec<ActionOutcome>,
+    pub substitutions: Vec<PlaceholderSubstitution>,
+}
+
+#[event]
+pub struct StreamCreated {
+    pub vault: Pubkey,
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub total_amount: u64,
+    pub start_date: i64,
+    pub cliff_date: i64,
+    pub end_date: i64,
+}
+
+#[event]
+pub struct StreamWithdrawn {
+    pub vault: Pubkey,
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub withdrawn_amount: u64,
+}
+
+#[event]
+pub struct StreamCancelled {
+    pub vault: Pubkey,
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub vested_amount: u64,
+    pub unvested_amount: u64,
+    pub cancelled_date: i64,
+}
+
+#[event]
+pub struct ProposalLimitsUpdated {
+    pub vault: Pubkey,
+    pub proposal_limits: ProposalLimits,
+}
+
+#[event]
+pub struct ProgramAllowlistUpdated {
+    pub vault: Pubkey,
+    pub enabled: bool,
+    pub programs: Vec<AllowedProgram>,
+}
+
+#[event]
+pub struct ProposalGuardsUpdated {
+    pub proposal: Pubkey,
+    pub pre_guards: Vec<Guard>,
+    pub post_guards: Vec<Guard>,
+}
+
+#[event]
+pub struct OutflowCapUpdated {
+    pub vault: Pubkey,
+    /// Set when the cap applies to a single proposal rather than to the vault.
+    pub proposal: Option<Pubkey>,
+    pub outflow_cap: Option<OutflowCap>,
+}
+
+#[event]
+pub struct ProposalTemplateCreated {
+    pub template: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub action_count: u16,
+}
+
+#[event]
+pub struct ProposalTemplateInstantiated {
+    pub template: Pubkey,
+    pub proposal: Pubkey,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct ProposalDependenciesUpdated {
+    pub proposal: Pubkey,
+    pub dependencies: Vec<Pubkey>,
+}
+
+#[event]
+pub struct ProposalBatchExecuted {
+    pub vault: Pubkey,
+    pub proposals: Vec<Pubkey>,
+    pub executed_date: i64,
+}
+
+#[event]
+pub struct DirectExecutionDisabled {
+    pub vault: Pubkey,
 }
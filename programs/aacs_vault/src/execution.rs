@@ -0,0 +1,448 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        instruction::Instruction,
+        program::{get_return_data, invoke_signed},
+    },
+    Owners,
+};
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{
+    error::ErrorCode,
+    events::ProposalActionFailed,
+    state::{
+        ActionDataBuffer, ActionOutcome, ActionResult, ActionView, EphemeralSignerSeeds, ExecutionPolicy,
+        PlaceholderSubstitution, ProgramAllowlist, Vault, VaultSignerSeeds, VaultSubAccount,
+    },
+};
+
+/// Invokes actions against an account key table. The table's accounts are passed, in
+/// table order, as the leading remaining accounts of the executing instruction.
+///
+/// The instruction and account buffers are reused across actions, since the bump
+/// allocator never frees and large proposals would otherwise exhaust the heap.
+pub struct ActionExecutor<'a, 'info> {
+    account_keys: Vec<Pubkey>,
+    accounts: &'a [AccountInfo<'info>],
+    vault: Pubkey,
+    /// Proposal being executed, if any.
+    proposal: Option<Pubkey>,
+    /// Main vault signer first, followed by sub-account signers as actions use them.
+    vault_signers: Vec<VaultSignerSeeds>,
+    /// Accounts holding the `VaultSubAccount` of every sub-account actions sign for.
+    sub_accounts: &'a [AccountInfo<'info>],
+    ephemeral_signers: Vec<EphemeralSignerSeeds>,
+    ix: Instruction,
+    account_infos: Vec<AccountInfo<'info>>,
+    /// Amounts written into placeholders of invoked actions.
+    substitutions: Vec<PlaceholderSubstitution>,
+}
+
+impl<'a, 'info> ActionExecutor<'a, 'info> {
+    pub fn new(
+        accounts: &'a [AccountInfo<'info>],
+        vault: Pubkey,
+        proposal: Option<Pubkey>,
+        vault_signer: VaultSignerSeeds,
+        ephemeral_signers: Vec<EphemeralSignerSeeds>,
+        sub_accounts: &'a [AccountInfo<'info>],
+    ) -> Self {
+        ActionExecutor {
+            account_keys: accounts.iter().map(|account| *account.key).collect(),
+            accounts,
+            vault,
+            proposal,
+            vault_signers: vec![vault_signer],
+            sub_accounts,
+            ephemeral_signers,
+            ix: Instruction {
+                program_id: Pubkey::default(),
+                accounts: vec![],
+                data: vec![],
+            },
+            account_infos: vec![],
+            substitutions: vec![],
+        }
+    }
+
+    /// Returns the leading remaining accounts after checking they match `account_keys`.
+    pub fn key_table_accounts(
+        remaining_accounts: &'a [AccountInfo<'info>],
+        account_keys: &[Pubkey],
+    ) -> Result<&'a [AccountInfo<'info>]> {
+        let accounts = remaining_accounts
+            .get(..account_keys.len())
+            .ok_or(ErrorCode::AccountKeyMismatch)?;
+
+        require!(
+            accounts
+                .iter()
+                .zip(account_keys.iter())
+                .all(|(account, pubkey)| account.key == pubkey),
+            ErrorCode::AccountKeyMismatch
+        );
+
+        Ok(accounts)
+    }
+
+    pub fn account_keys(&self) -> &[Pubkey] {
+        &self.account_keys
+    }
+
+    /// Hands over the placeholder substitutions made so far.
+    pub fn take_substitutions(&mut self) -> Vec<PlaceholderSubstitution> {
+        std::mem::take(&mut self.substitutions)
+    }
+
+    /// Switches to another key table, keeping the signers and buffers.
+    pub fn set_accounts(&mut self, accounts: &'a [AccountInfo<'info>]) {
+        self.account_keys.clear();
+        self.account_keys
+            .extend(accounts.iter().map(|account| *account.key));
+        self.accounts = accounts;
+    }
+
+    /// Whether `pubkey` is a PDA this program signs for: the main vault signer or one of
+    /// the ephemeral signers. Sub-account signers are marked on their account specs.
+    fn is_program_signer(&self, pubkey: &Pubkey) -> bool {
+        self.vault_signers[0].address == *pubkey
+            || self
+                .ephemeral_signers
+                .iter()
+                .any(|signer| signer.address == *pubkey)
+    }
+
+    /// Runs every check an action must pass before anything is invoked, reporting the
+    /// action index and target of a failing action.
+    pub fn validate(
+        &mut self,
+        action_index: u16,
+        action: &ActionView,
+        vault_account: &Vault,
+        program_allowlist: Option<&ProgramAllowlist>,
+    ) -> Result<()> {
+        self.load_sub_account_signers(action)
+            .and_then(|_| {
+                action.validate_targets(&self.account_keys, &self.vault, self.proposal.as_ref())
+            })
+            .and_then(|_| {
+                self.with_data(action, |data| {
+                    let program_id = action.program_id(&self.account_keys)?;
+                    let pinned_len =
+                        ProgramAllowlist::pinned_prefix_len(vault_account, program_allowlist, program_id);
+
+                    action.validate_placeholders(&self.account_keys, data, pinned_len)?;
+                    ProgramAllowlist::check_target(
+                        vault_account,
+                        program_allowlist,
+                        program_id,
+                        data,
+                    )
+                })
+            })
+            .and_then(|_| self.check_signers(action_index, action))
+            .inspect_err(|error| self.report_failure(action_index, action, error_code(error)))
+    }
+
+    /// Runs `f` on the instruction data of `action`, read from its data buffer if it has
+    /// one. The buffer must be attached to the proposal being executed and hold the data
+    /// the action committed to.
+    fn with_data<T>(&self, action: &ActionView, f: impl FnOnce(&[u8]) -> Result<T>) -> Result<T> {
+        let Some(data_buffer) = action.data_buffer else {
+            return f(action.data);
+        };
+
+        let account = self
+            .accounts
+            .get(data_buffer.account_index as usize)
+            .ok_or(ErrorCode::InvalidAccountIndex)?;
+        let data = account.try_borrow_data()?;
+
+        f(ActionDataBuffer::referenced_data(
+            account,
+            &data,
+            self.proposal.as_ref(),
+            &data_buffer,
+        )?)
+    }
+
+    /// Derives the signers of the sub-accounts `action` signs for. Only opened sub-accounts
+    /// are signed for.
+    fn load_sub_account_signers(&mut self, action: &ActionView) -> Result<()> {
+        for account_spec in action.account_specs() {
+            let Some(index) = account_spec?.sub_account_index else {
+                continue;
+            };
+
+            if self.sub_account_signer(index).is_none() {
+                self.vault_signers
+                    .push(VaultSubAccount::find_signer(self.sub_accounts, self.vault, index)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn sub_account_signer(&self, index: u8) -> Option<&VaultSignerSeeds> {
+        self.vault_signers
+            .iter()
+            .find(|signer| signer.index() == Some(index))
+    }
+
+    /// Checks that every signer `action` requires, other than the PDAs this program signs
+    /// for, signed the transaction.
+    fn check_signers(&self, action_index: u16, action: &ActionView) -> Result<()> {
+        for account_spec in action.account_specs() {
+            let account_spec = account_spec?;
+
+            if !account_spec.is_signer || account_spec.sub_account_index.is_some() {
+                continue;
+            }
+
+            let pubkey = account_spec.pubkey(&self.account_keys)?;
+            if self.is_program_signer(pubkey)
+                || self.accounts[account_spec.account_index as usize].is_signer
+            {
+                continue;
+            }
+
+            msg!("Action {} requires {} to sign", action_index, pubkey);
+            return err!(ErrorCode::MissingActionSigner);
+        }
+
+        Ok(())
+    }
+
+    /// Logs the failure of action `action_index` and records it in a `ProposalActionFailed`
+    /// event, which remains in the logs of the failed transaction.
+    fn report_failure(&self, action_index: u16, action: &ActionView, error_code: u64) {
+        let program_id = action.program_id(&self.account_keys).ok().copied();
+
+        msg!(
+            "Action {} targeting {:?} failed with error code {}",
+            action_index,
+            program_id,
+            error_code
+        );
+
+        emit!(ProposalActionFailed {
+            vault: self.vault,
+            proposal: self.proposal,
+            action_index,
+            program_id,
+            error_code,
+        });
+    }
+
+    /// Whether `action` can be invoked without hitting a known failure. Token programs
+    /// never create the accounts they write, so every writable account a token program
+    /// action does not sign for must already be one of that program's accounts, which a
+    /// closed or missing token account is not. Actions of other programs may create what
+    /// they write, as payouts to fresh wallets or idempotent token account creations do,
+    /// and are always invoked.
+    fn precheck(&self, action: &ActionView) -> Result<bool> {
+        let program_id = action.program_id(&self.account_keys)?;
+
+        if !TokenAccount::owners().contains(program_id) {
+            return Ok(true);
+        }
+
+        for account_spec in action.account_specs() {
+            let account_spec = account_spec?;
+
+            if !account_spec.is_writable
+                || account_spec.is_signer
+                || account_spec.sub_account_index.is_some()
+                || self.is_program_signer(account_spec.pubkey(&self.account_keys)?)
+            {
+                continue;
+            }
+
+            let account = &self.accounts[account_spec.account_index as usize];
+            if account.owner != program_id || account.lamports() == 0 {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Invokes `actions`, numbered from `first_action_index`, according to their execution
+    /// policies. Appends an outcome per action and the return data of invoked actions.
+    pub fn execute_all(
+        &mut self,
+        first_action_index: u16,
+        actions: &[ActionView],
+        outcomes: &mut Vec<ActionOutcome>,
+        results: &mut Vec<ActionResult>,
+    ) -> Result<()> {
+        let mut position = 0;
+
+        while position < actions.len() {
+            // Group members are consecutive and are prechecked together right before the
+            // group runs, so earlier actions can still prepare their accounts.
+            let run_len = match actions[position].policy {
+                ExecutionPolicy::Group { id } => actions[position..]
+                    .iter()
+                    .take_while(|action| action.policy == ExecutionPolicy::Group { id })
+                    .count(),
+                _ => 1,
+            };
+            let run = &actions[position..position + run_len];
+
+            let invoke = match run[0].policy {
+                ExecutionPolicy::Atomic => true,
+                _ => {
+                    let mut passed = true;
+                    for action in run {
+                        passed = passed && self.precheck(action)?;
+                    }
+                    passed
+                }
+            };
+
+            for (offset, action) in run.iter().enumerate() {
+                let action_index = first_action_index + (position + offset) as u16;
+
+                if !invoke {
+                    msg!("Skipping action {} after failed prechecks", action_index);
+                    outcomes.push(ActionOutcome::Skipped);
+                    continue;
+                }
+
+                if let Some((program_id, data)) = self.execute(action_index, action)? {
+                    results.push(ActionResult {
+                        action_index,
+                        program_id,
+                        data,
+                    });
+                }
+                outcomes.push(ActionOutcome::Executed);
+            }
+
+            position += run_len;
+        }
+
+        Ok(())
+    }
+
+    /// Fills in the placeholders of `action` and invokes it signed by every vault and
+    /// ephemeral signer, returning the return data it left behind.
+    ///
+    /// A failing program aborts the whole transaction inside the runtime, so the action
+    /// is logged before it is invoked to attribute such failures. Errors the runtime hands
+    /// back are reported and wrapped in `ActionFailed`.
+    pub fn execute(
+        &mut self,
+        action_index: u16,
+        action: &ActionView,
+    ) -> Result<Option<(Pubkey, Vec<u8>)>> {
+        self.ix.program_id = *action.program_id(&self.account_keys)?;
+
+        msg!("Invoking action {} on {}", action_index, self.ix.program_id);
+
+        self.ix.accounts.clear();
+
+        let mut data = std::mem::take(&mut self.ix.data);
+        data.clear();
+        self.with_data(action, |bytes| {
+            data.extend_from_slice(bytes);
+            Ok(())
+        })?;
+
+        for placeholder in action.placeholders() {
+            let placeholder = placeholder?;
+            let amount = placeholder.resolve(self.accounts, &self.vault_signers[0].address)?;
+            placeholder.write(&mut data, amount)?;
+
+            msg!(
+                "Action {} placeholder at {} set to {}",
+                action_index,
+                placeholder.offset,
+                amount
+            );
+
+            self.substitutions.push(PlaceholderSubstitution {
+                action_index,
+                offset: placeholder.offset,
+                amount,
+            });
+        }
+        self.ix.data = data;
+
+        self.account_infos.clear();
+        self.account_infos
+            .push(self.accounts[action.program_id_index as usize].clone());
+
+        for account_spec in action.account_specs() {
+            let account_spec = account_spec?;
+            let mut account_meta = account_spec.to_account_meta(&self.account_keys)?;
+
+            if self.is_program_signer(&account_meta.pubkey) {
+                account_meta.is_signer = true;
+            }
+
+            if let Some(index) = account_spec.sub_account_index {
+                // Loaded when the action was validated.
+                let sub_account_signer = self
+                    .sub_account_signer(index)
+                    .ok_or(ErrorCode::SubAccountNotOpened)?;
+
+                require_keys_eq!(
+                    account_meta.pubkey,
+                    sub_account_signer.address,
+                    ErrorCode::InvalidVaultSigner
+                );
+
+                account_meta.is_signer = true;
+            }
+
+            self.account_infos
+                .push(self.accounts[account_spec.account_index as usize].clone());
+            self.ix.accounts.push(account_meta);
+        }
+
+        let seeds = self
+            .vault_signers
+            .iter()
+            .map(|signer| signer.seeds())
+            .chain(self.ephemeral_signers.iter().map(|signer| signer.seeds()))
+            .collect::<Vec<_>>();
+        let signer = seeds
+            .iter()
+            .map(|seeds| seeds.as_slice())
+            .collect::<Vec<_>>();
+
+        if let Err(program_error) = invoke_signed(&self.ix, &self.account_infos, &signer) {
+            let error_code = u64::from(program_error);
+            self.report_failure(action_index, action, error_code);
+
+            return Err(action_failed(action_index, &self.ix.program_id, error_code));
+        }
+
+        Ok(get_return_data())
+    }
+}
+
+/// Numeric code of an error as it appears in the transaction status.
+fn error_code(error: &Error) -> u64 {
+    match error {
+        Error::AnchorError(anchor_error) => anchor_error.error_code_number as u64,
+        Error::ProgramError(program_error) => u64::from(program_error.program_error.clone()),
+    }
+}
+
+/// `ActionFailed` with a message naming the failing action, its target and inner error.
+fn action_failed(action_index: u16, program_id: &Pubkey, error_code: u64) -> Error {
+    let mut error = error!(ErrorCode::ActionFailed);
+
+    if let Error::AnchorError(anchor_error) = &mut error {
+        anchor_error.error_msg = format!(
+            "Action {} targeting {} failed with error code {}",
+            action_index, program_id, error_code
+        );
+    }
+
+    error
+}
@@ -1,17 +1,19 @@
 use anchor_lang::prelude::*;
 
 use crate::{
+    constants::PROPOSAL_ACTIONS_PREFIX,
     error::ErrorCode,
     events::ActionsAppended,
-    state::{AccountSpec, Action, Proposal, Vault},
+    state::{Action, ProgramAllowlist, Proposal, ProposalActions, ProposalKind, Vault},
 };
 
-use super::ParamAction;
+use super::{validate_new_actions, write_proposal_actions, ParamAction};
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct AppendActionsParams {
+    /// Account key table of the new chunk, which `actions` index into.
+    pub account_keys: Vec<Pubkey>,
     pub actions: Vec<ParamAction>,
-    pub proposal_account_size: u32,
 }
 
 #[derive(Accounts)]
@@ -21,7 +23,8 @@ pub struct AppendActions<'info> {
     pub proposer: Signer<'info>,
 
     #[account(
-        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned
+        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
     )]
     pub vault: Box<Account<'info, Vault>>,
 
@@ -29,12 +32,26 @@ pub struct AppendActions<'info> {
         mut,
         has_one = vault @ErrorCode::ProposalNotOwned,
         constraint = !proposal.is_executed @ErrorCode::ProposalAlreadyExecuted,
-        realloc = 8 + params.proposal_account_size as usize,
-        realloc::payer = proposer,
-        realloc::zero = false,
+        constraint = proposal.kind == ProposalKind::Stored @ErrorCode::WrongProposalKind,
     )]
     pub proposal: Box<Account<'info, Proposal>>,
 
+    #[account(
+        init,
+        seeds = [
+            PROPOSAL_ACTIONS_PREFIX.as_ref(),
+            proposal.key().as_ref(),
+            proposal.chunk_count.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + ParamAction::chunk_data_size(params.account_keys.len(), &params.actions),
+        payer = proposer
+    )]
+    pub proposal_actions: AccountLoader<'info, ProposalActions>,
+
+    #[account(has_one = vault)]
+    pub program_allowlist: Option<Box<Account<'info, ProgramAllowlist>>>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -42,38 +59,36 @@ pub fn append_actions_handler(
     ctx: Context<AppendActions>,
     params: AppendActionsParams,
 ) -> Result<()> {
+    let vault_account = &ctx.accounts.vault;
     let proposal_account = &mut ctx.accounts.proposal;
 
-    let actions = params
-        .actions
-        .iter()
-        .map(|acc| {
-            let account_specs = acc
-                .account_specs
-                .iter()
-                .map(AccountSpec::from)
-                .collect::<Vec<_>>();
-
-            Action::new(acc.program_id, account_specs, acc.data.clone())
-        })
-        .collect::<Vec<_>>();
-
-    let mut new_actions = proposal_account.actions.clone();
-    new_actions.append(&mut actions.clone());
+    let actions = params.actions.iter().map(Action::from).collect::<Vec<_>>();
 
-    let required_data_len = Proposal::calculate_data_size(&proposal_account.name, &new_actions);
+    vault_account
+        .proposal_limits
+        .check_actions(proposal_account.action_count, &actions)?;
 
-    require_gte!(
-        params.proposal_account_size as usize,
-        required_data_len,
-        ErrorCode::InsufficientDataAllocationForProposal
-    );
+    validate_new_actions(
+        vault_account,
+        ctx.accounts.program_allowlist.as_deref().map(|allowlist| &**allowlist),
+        &proposal_account.key(),
+        &params.account_keys,
+        &actions,
+    )?;
 
-    proposal_account.actions = new_actions;
+    let chunk_index = write_proposal_actions(
+        proposal_account,
+        &ctx.accounts.proposal_actions,
+        ctx.bumps.proposal_actions,
+        &params.account_keys,
+        &actions,
+    )?;
 
     emit!(ActionsAppended {
+        account_keys: params.account_keys,
         actions,
         proposal: proposal_account.key(),
+        chunk_index,
     });
 
     Ok(())
@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode,
+    events::ActionDataBufferAttached,
+    state::{ActionDataBuffer, Proposal, Vault},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AttachBufferParams {
+    pub data_len: u32,
+    pub data_hash: [u8; 32],
+}
+
+#[derive(Accounts)]
+pub struct AttachBuffer<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        constraint = &vault.owner == authority.key @ErrorCode::VaultNotOwned,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        has_one = vault @ErrorCode::ProposalNotOwned,
+        constraint = !proposal.is_executed @ErrorCode::ProposalAlreadyExecuted,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    #[account(mut, has_one = authority, has_one = vault)]
+    pub buffer: AccountLoader<'info, ActionDataBuffer>,
+}
+
+/// Freezes the buffer once its data has the expected length and hash, and lets actions of
+/// the proposal read it.
+pub fn attach_buffer_handler(ctx: Context<AttachBuffer>, params: AttachBufferParams) -> Result<()> {
+    let data_hash = {
+        let buffer_info = ctx.accounts.buffer.to_account_info();
+        let data = buffer_info.try_borrow_data()?;
+        let (buffer, buffer_data) = ActionDataBuffer::split(&data)?;
+
+        require!(!buffer.is_attached(), ErrorCode::ActionDataBufferFrozen);
+        require_eq!(
+            buffer.data_len,
+            params.data_len,
+            ErrorCode::ActionDataBufferMismatch
+        );
+
+        ActionDataBuffer::hash_data(buffer_data)
+    };
+
+    require!(
+        data_hash == params.data_hash,
+        ErrorCode::ActionDataBufferMismatch
+    );
+
+    let mut buffer = ctx.accounts.buffer.load_mut()?;
+    buffer.proposal = ctx.accounts.proposal.key();
+    buffer.data_hash = data_hash;
+    buffer.is_attached = 1;
+
+    emit!(ActionDataBufferAttached {
+        buffer: ctx.accounts.buffer.key(),
+        proposal: buffer.proposal,
+        data_len: buffer.data_len,
+        data_hash,
+    });
+
+    Ok(())
+}
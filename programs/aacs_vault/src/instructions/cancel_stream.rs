@@ -0,0 +1,233 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    associated_token::{
+        create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create,
+    },
+    token::{close_account, CloseAccount, Token},
+    token_interface::{Mint, TokenAccount},
+};
+
+use crate::{
+    constants::{STREAM_PREFIX, VAULT_SIGNER_PREFIX},
+    error::ErrorCode,
+    events::StreamCancelled,
+    state::{Stream, Vault},
+};
+
+use super::pay_out_stream;
+
+#[derive(Accounts)]
+pub struct CancelStream<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        constraint = &vault.owner == owner.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SIGNER_PREFIX.as_ref(), vault.key().as_ref()],
+        bump = vault.signer_bump,
+    )]
+    /// CHECK: vault_signer is a PDA program signer. Data is never read or written to
+    pub vault_signer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = vault,
+        has_one = recipient,
+        seeds = [
+            STREAM_PREFIX.as_ref(),
+            vault.key().as_ref(),
+            stream.stream_id.to_le_bytes().as_ref()
+        ],
+        bump = stream.bump,
+        close = owner,
+    )]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(mut)]
+    /// CHECK: only paid out to, and bound to the stream by `has_one`. May be a PDA.
+    pub recipient: UncheckedAccount<'info>,
+
+    pub token_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vault_signer,
+    )]
+    pub vault_signer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = stream,
+    )]
+    pub stream_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Created with the vault signer paying if the recipient has closed it or never had
+    /// one, so the recipient cannot hold up a cancellation.
+    #[account(mut)]
+    /// CHECK: checked to be the recipient's associated token account before it is created
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Pays out whatever has vested but not been withdrawn, returns the unvested remainder from
+/// the escrow to the vault signer and closes the stream along with its token escrow.
+pub fn cancel_stream_handler(ctx: Context<CancelStream>) -> Result<()> {
+    let vault_account = &ctx.accounts.vault;
+    let stream_account = &ctx.accounts.stream;
+
+    let now = Clock::get()?.unix_timestamp;
+    let vested_amount = stream_account.vested_amount(now);
+    let amount = vested_amount.saturating_sub(stream_account.withdrawn_amount);
+    let unvested_amount = stream_account.total_amount - vested_amount;
+
+    if amount > 0 && stream_account.mint.is_some() {
+        create_recipient_token_account(&ctx)?;
+    }
+
+    let recipient_token_account = ctx
+        .accounts
+        .recipient_token_account
+        .as_ref()
+        .map(|account| account.to_account_info());
+    let vault_signer_token_account = ctx
+        .accounts
+        .vault_signer_token_account
+        .as_ref()
+        .map(|account| account.to_account_info());
+
+    for (to_account, to_token_account, amount) in [
+        (
+            ctx.accounts.recipient.to_account_info(),
+            recipient_token_account.as_ref(),
+            amount,
+        ),
+        (
+            ctx.accounts.vault_signer.to_account_info(),
+            vault_signer_token_account.as_ref(),
+            unvested_amount,
+        ),
+    ] {
+        if amount > 0 {
+            pay_out_stream(
+                stream_account,
+                &to_account,
+                &ctx.accounts.token_mint,
+                &ctx.accounts.stream_token_account,
+                to_token_account,
+                &ctx.accounts.token_program,
+                amount,
+            )?;
+        }
+    }
+
+    if stream_account.mint.is_some() {
+        let (Some(stream_token_account), Some(token_program)) =
+            (&ctx.accounts.stream_token_account, &ctx.accounts.token_program)
+        else {
+            return err!(ErrorCode::StreamMintMismatch);
+        };
+
+        let vault_key = vault_account.key();
+        let stream_id = stream_account.stream_id.to_le_bytes();
+
+        let seeds = &[
+            STREAM_PREFIX.as_ref(),
+            vault_key.as_ref(),
+            stream_id.as_ref(),
+            &[stream_account.bump],
+        ];
+
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_context = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: stream_token_account.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: stream_account.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        close_account(cpi_context)?;
+    }
+
+    emit!(StreamCancelled {
+        vault: vault_account.key(),
+        stream: stream_account.key(),
+        recipient: stream_account.recipient,
+        vested_amount,
+        unvested_amount,
+        cancelled_date: now,
+    });
+
+    Ok(())
+}
+
+/// Creates the recipient's associated token account for the stream mint unless it exists,
+/// with the vault signer paying the rent.
+fn create_recipient_token_account(ctx: &Context<CancelStream>) -> Result<()> {
+    let (
+        Some(token_mint),
+        Some(recipient_token_account),
+        Some(token_program),
+        Some(associated_token_program),
+    ) = (
+        &ctx.accounts.token_mint,
+        &ctx.accounts.recipient_token_account,
+        &ctx.accounts.token_program,
+        &ctx.accounts.associated_token_program,
+    )
+    else {
+        return err!(ErrorCode::StreamMintMismatch);
+    };
+
+    require_keys_eq!(
+        recipient_token_account.key(),
+        get_associated_token_address_with_program_id(
+            ctx.accounts.recipient.key,
+            &token_mint.key(),
+            token_program.key,
+        ),
+        ErrorCode::StreamMintMismatch
+    );
+
+    let vault_key = ctx.accounts.vault.key();
+
+    let seeds = &[
+        VAULT_SIGNER_PREFIX.as_ref(),
+        vault_key.as_ref(),
+        &[ctx.accounts.vault.signer_bump],
+    ];
+
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_context = CpiContext::new_with_signer(
+        associated_token_program.to_account_info(),
+        Create {
+            payer: ctx.accounts.vault_signer.to_account_info(),
+            associated_token: recipient_token_account.to_account_info(),
+            authority: ctx.accounts.recipient.to_account_info(),
+            mint: token_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: token_program.to_account_info(),
+        },
+        signer_seeds,
+    );
+
+    create_idempotent(cpi_context)
+}
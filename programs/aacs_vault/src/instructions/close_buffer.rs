@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode,
+    state::{ActionDataBuffer, Proposal},
+};
+
+#[derive(Accounts)]
+pub struct CloseBuffer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = authority, close = authority)]
+    pub buffer: AccountLoader<'info, ActionDataBuffer>,
+
+    /// The proposal an attached buffer belongs to.
+    pub proposal: Option<Box<Account<'info, Proposal>>>,
+}
+
+/// Closes a buffer that is not attached, or whose proposal was executed or has expired.
+pub fn close_buffer_handler(ctx: Context<CloseBuffer>) -> Result<()> {
+    let buffer = ctx.accounts.buffer.load()?;
+
+    if !buffer.is_attached() {
+        return Ok(());
+    }
+
+    let proposal = ctx
+        .accounts
+        .proposal
+        .as_ref()
+        .ok_or(ErrorCode::ActionDataBufferInUse)?;
+
+    require_keys_eq!(
+        proposal.key(),
+        buffer.proposal,
+        ErrorCode::ActionDataBufferInUse
+    );
+    require!(
+        proposal.is_executed || proposal.expiry_date < Clock::get()?.unix_timestamp,
+        ErrorCode::ActionDataBufferInUse
+    );
+
+    Ok(())
+}
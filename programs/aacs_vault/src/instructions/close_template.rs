@@ -0,0 +1,17 @@
+use anchor_lang::prelude::*;
+
+use crate::state::ProposalTemplate;
+
+#[derive(Accounts)]
+pub struct CloseTemplate<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = authority, close = authority)]
+    pub template: Box<Account<'info, ProposalTemplate>>,
+}
+
+/// Proposals already instantiated from the template are unaffected.
+pub fn close_template_handler(_ctx: Context<CloseTemplate>) -> Result<()> {
+    Ok(())
+}
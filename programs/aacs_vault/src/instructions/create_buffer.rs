@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode,
+    events::ActionDataBufferCreated,
+    state::{ActionDataBuffer, Vault},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateBufferParams {
+    /// Final length of the action data the buffer will hold.
+    pub data_len: u32,
+}
+
+#[derive(Accounts)]
+#[instruction(params: CreateBufferParams)]
+pub struct CreateBuffer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        constraint = &vault.owner == authority.key @ErrorCode::VaultNotOwned,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init,
+        signer,
+        space = 8 + ActionDataBuffer::calculate_data_size(params.data_len as usize),
+        payer = authority
+    )]
+    pub buffer: AccountLoader<'info, ActionDataBuffer>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_buffer_handler(ctx: Context<CreateBuffer>, params: CreateBufferParams) -> Result<()> {
+    let mut buffer = ctx.accounts.buffer.load_init()?;
+    buffer.vault = ctx.accounts.vault.key();
+    buffer.authority = ctx.accounts.authority.key();
+    buffer.data_len = params.data_len;
+
+    emit!(ActionDataBufferCreated {
+        buffer: ctx.accounts.buffer.key(),
+        vault: buffer.vault,
+        authority: buffer.authority,
+        data_len: buffer.data_len,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::DEFAULT_FLOW_EXPIRY_DURATION,
+    error::ErrorCode,
+    events::CommittedProposalCreated,
+    state::{EphemeralSignerSeeds, Proposal, ProposalKind, ProposalStage, Vault},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateCommittedProposalParams {
+    pub name: String,
+    /// `ProposalActions::chain_hash` of the actions as a single chunk, starting from zero.
+    pub actions_hash: [u8; 32],
+    pub action_count: u16,
+    /// Size of the account keys and serialized actions, checked when they are supplied.
+    pub actions_size: u32,
+    pub ephemeral_signer_count: u8,
+    pub co_signers: Vec<Pubkey>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: CreateCommittedProposalParams)]
+pub struct CreateCommittedProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init,
+        signer,
+        space = 8 + Proposal::calculate_data_size(
+            &params.name,
+            params.ephemeral_signer_count as usize,
+            params.co_signers.len(),
+            0,
+            0,
+            None,
+            0,
+        ),
+        payer = proposer
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_committed_proposal_handler(
+    ctx: Context<CreateCommittedProposal>,
+    params: CreateCommittedProposalParams,
+) -> Result<()> {
+    let proposal_account = &mut ctx.accounts.proposal;
+    let vault_account = &ctx.accounts.vault;
+
+    // The actions themselves are only seen, and validated, when the proposal is executed.
+    vault_account.proposal_limits.check_name(&params.name)?;
+    vault_account
+        .proposal_limits
+        .check_action_count(params.action_count as usize)?;
+
+    let ephemeral_signers =
+        EphemeralSignerSeeds::find_all(proposal_account.key(), params.ephemeral_signer_count)?;
+
+    proposal_account.ephemeral_signer_bumps = ephemeral_signers
+        .iter()
+        .map(|ephemeral_signer| ephemeral_signer.bump())
+        .collect();
+    proposal_account.co_signers = params.co_signers;
+    proposal_account.name = params.name;
+    proposal_account.kind = ProposalKind::Committed;
+    proposal_account.action_count = params.action_count;
+    proposal_account.actions_hash = params.actions_hash;
+    proposal_account.actions_size = params.actions_size;
+    proposal_account.vault = vault_account.key();
+    proposal_account.created_date = Clock::get()?.unix_timestamp;
+    proposal_account.expiry_date = proposal_account.created_date + DEFAULT_FLOW_EXPIRY_DURATION;
+    proposal_account.proposal_stage = ProposalStage::Draft;
+
+    emit!(CommittedProposalCreated {
+        proposal: proposal_account.key(),
+        vault: vault_account.key(),
+        name: proposal_account.name.clone(),
+        created_date: proposal_account.created_date,
+        ephemeral_signers: ephemeral_signers
+            .iter()
+            .map(|ephemeral_signer| ephemeral_signer.address)
+            .collect(),
+        co_signers: proposal_account.co_signers.clone(),
+        action_count: proposal_account.action_count,
+        actions_hash: proposal_account.actions_hash,
+    });
+
+    Ok(())
+}
@@ -1,41 +1,104 @@
+use std::mem::size_of;
+
 use anchor_lang::prelude::*;
 
 use crate::{
-    constants::DEFAULT_FLOW_EXPIRY_DURATION, 
+    constants::{DEFAULT_FLOW_EXPIRY_DURATION, PROPOSAL_ACTIONS_PREFIX}, 
     error::ErrorCode, 
     events::ProposalCreated, 
-    state::{AccountSpec, Action, Proposal, ProposalStage, Vault}
+    state::{
+        validate_account_keys, validate_action_groups, AccountSpec, Action, DataBufferRef,
+        EphemeralSignerSeeds, ExecutionPolicy, Placeholder, ProgramAllowlist, Proposal,
+        ProposalActions, ProposalStage, Vault
+    }
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct ParamAccountSpec {
-    pub pubkey: Pubkey,
+    pub account_index: u8,
     pub is_signer: bool,
     pub is_writable: bool,
+    pub sub_account_index: Option<u8>,
 }
 
 impl From<&ParamAccountSpec> for AccountSpec {
     fn from(account_spec: &ParamAccountSpec) -> Self {
         AccountSpec {
-            pubkey: account_spec.pubkey,
+            account_index: account_spec.account_index,
             is_signer: account_spec.is_signer,
             is_writable: account_spec.is_writable,
+            sub_account_index: account_spec.sub_account_index,
         }
     }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct ParamAction {
-    pub program_id: Pubkey,
+    pub program_id_index: u8,
     pub account_specs: Vec<ParamAccountSpec>,
     pub data: Vec<u8>,
+    pub data_buffer: Option<DataBufferRef>,
+    pub placeholders: Vec<Placeholder>,
+    pub policy: ExecutionPolicy,
+}
+
+impl ParamAction {
+    pub fn get_data_size(&self) -> usize {
+        let account_specs_size: usize = self
+            .account_specs
+            .iter()
+            .map(|account_spec| 4 + account_spec.sub_account_index.map_or(0, |_| 1))
+            .sum();
+        let placeholders_size: usize = self
+            .placeholders
+            .iter()
+            .map(|placeholder| placeholder.get_data_size())
+            .sum();
+
+        1 + 4
+            + account_specs_size
+            + 4
+            + self.data.len()
+            + 1
+            + self.data_buffer.map_or(0, |_| DataBufferRef::INIT_SPACE)
+            + 4
+            + placeholders_size
+            + self.policy.get_data_size()
+    }
+
+    /// Size of a `ProposalActions` chunk holding `actions` against `account_key_count` keys.
+    pub fn chunk_data_size(account_key_count: usize, actions: &[ParamAction]) -> usize {
+        let actions_size = actions.iter().map(|action| action.get_data_size()).sum();
+        ProposalActions::calculate_data_size(account_key_count, actions_size)
+    }
+}
+
+impl From<&ParamAction> for Action {
+    fn from(action: &ParamAction) -> Self {
+        let account_specs = action
+            .account_specs
+            .iter()
+            .map(AccountSpec::from)
+            .collect::<Vec<_>>();
+
+        Action::new(
+            action.program_id_index,
+            account_specs,
+            action.data.clone(),
+            action.data_buffer,
+            action.placeholders.clone(),
+            action.policy,
+        )
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct CreateProposalParams {
     pub name: String,
+    pub account_keys: Vec<Pubkey>,
     pub actions: Vec<ParamAction>,
-    pub proposal_account_size: u32,
+    pub ephemeral_signer_count: u8,
+    pub co_signers: Vec<Pubkey>,
 }
 
 #[derive(Accounts)]
@@ -45,18 +108,39 @@ pub struct CreateProposal<'info> {
     pub proposer: Signer<'info>,
     
     #[account(
-        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned
+        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
     )]
     pub vault: Box<Account<'info, Vault>>,
     
     #[account(
         init,
         signer, 
-        space = 8 + params.proposal_account_size as usize, 
+        space = 8 + Proposal::calculate_data_size(
+            &params.name,
+            params.ephemeral_signer_count as usize,
+            params.co_signers.len(),
+            0,
+            0,
+            None,
+            0,
+        ),
         payer = proposer
     )]
     pub proposal: Box<Account<'info, Proposal>>,
 
+    #[account(
+        init,
+        seeds = [PROPOSAL_ACTIONS_PREFIX.as_ref(), proposal.key().as_ref(), 0u16.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + ParamAction::chunk_data_size(params.account_keys.len(), &params.actions),
+        payer = proposer
+    )]
+    pub proposal_actions: AccountLoader<'info, ProposalActions>,
+
+    #[account(has_one = vault)]
+    pub program_allowlist: Option<Box<Account<'info, ProgramAllowlist>>>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -64,42 +148,156 @@ pub fn create_proposal_handler(ctx: Context<CreateProposal>, params: CreatePropo
     let proposal_account = &mut ctx.accounts.proposal;
     let vault_account = &ctx.accounts.vault;
 
-    let actions = params
-        .actions
+    let actions = params.actions.iter().map(Action::from).collect::<Vec<_>>();
+
+    vault_account.proposal_limits.check_name(&params.name)?;
+    vault_account.proposal_limits.check_actions(0, &actions)?;
+
+    validate_new_actions(
+        vault_account,
+        ctx.accounts.program_allowlist.as_deref().map(|allowlist| &**allowlist),
+        &proposal_account.key(),
+        &params.account_keys,
+        &actions,
+    )?;
+
+    let ephemeral_signers =
+        EphemeralSignerSeeds::find_all(proposal_account.key(), params.ephemeral_signer_count)?;
+
+    init_proposal(
+        proposal_account,
+        &ctx.accounts.proposal_actions,
+        ctx.bumps.proposal_actions,
+        vault_account.key(),
+        params.name,
+        &ephemeral_signers,
+        params.co_signers,
+        params.account_keys,
+        actions,
+    )
+}
+
+/// Sets up a new draft proposal of `vault` with its ephemeral signers and co-signers,
+/// writes `actions` to its first chunk and emits `ProposalCreated`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn init_proposal(
+    proposal_account: &mut Account<Proposal>,
+    proposal_actions: &AccountLoader<ProposalActions>,
+    proposal_actions_bump: u8,
+    vault: Pubkey,
+    name: String,
+    ephemeral_signers: &[EphemeralSignerSeeds],
+    co_signers: Vec<Pubkey>,
+    account_keys: Vec<Pubkey>,
+    actions: Vec<Action>,
+) -> Result<()> {
+    proposal_account.ephemeral_signer_bumps = ephemeral_signers
         .iter()
-        .map(|acc| {
-            let account_specs = acc
-                .account_specs
-                .iter()
-                .map(AccountSpec::from)
-                .collect::<Vec<_>>();
-
-            Action::new(acc.program_id, account_specs, acc.data.clone())
-        })
-        .collect::<Vec<_>>();
-
-    let required_proposal_size = Proposal::calculate_data_size(&params.name, &actions);
-    
-    require_gte!(
-        params.proposal_account_size as usize, 
-        required_proposal_size, 
-        ErrorCode::InsufficientDataAllocationForProposal
-    );
-
-    proposal_account.name = params.name;
-    proposal_account.actions = actions.clone();
-    proposal_account.vault = vault_account.key();
+        .map(|ephemeral_signer| ephemeral_signer.bump())
+        .collect();
+    proposal_account.co_signers = co_signers;
+    proposal_account.name = name;
+    proposal_account.vault = vault;
     proposal_account.created_date = Clock::get()?.unix_timestamp;
     proposal_account.expiry_date = proposal_account.created_date + DEFAULT_FLOW_EXPIRY_DURATION;
     proposal_account.proposal_stage = ProposalStage::Draft;
 
+    write_proposal_actions(
+        proposal_account,
+        proposal_actions,
+        proposal_actions_bump,
+        &account_keys,
+        &actions,
+    )?;
+
     emit!(ProposalCreated {
+        account_keys,
         actions,
         created_date: proposal_account.created_date,
         name: proposal_account.name.clone(),
+        ephemeral_signers: ephemeral_signers
+            .iter()
+            .map(|ephemeral_signer| ephemeral_signer.address)
+            .collect(),
+        co_signers: proposal_account.co_signers.clone(),
         proposal: proposal_account.key(),
-        vault: vault_account.key()
+        vault
     });
 
     Ok(())
 }
+
+/// Initializes the next `ProposalActions` chunk of the proposal with `account_keys` and
+/// `actions`, and records it on the proposal. Returns the index of the chunk.
+pub(crate) fn write_proposal_actions(
+    proposal_account: &mut Account<Proposal>,
+    proposal_actions: &AccountLoader<ProposalActions>,
+    bump: u8,
+    account_keys: &[Pubkey],
+    actions: &[Action],
+) -> Result<u16> {
+    let action_count = u16::try_from(actions.len()).map_err(|_| ErrorCode::TooManyActions)?;
+    proposal_account.claim_action_groups(actions.iter().map(|action| Ok(action.view())))?;
+
+    let actions_len: usize = actions.iter().map(|action| action.get_data_size()).sum();
+
+    {
+        let mut header = proposal_actions.load_init()?;
+        header.proposal = proposal_account.key();
+        header.prev_actions_hash = proposal_account.actions_hash;
+        header.actions_len = actions_len as u32;
+        header.index = proposal_account.chunk_count;
+        header.account_key_count = account_keys.len() as u16;
+        header.action_count = action_count;
+        header.bump = bump;
+    }
+
+    let proposal_actions_info = proposal_actions.to_account_info();
+    let mut data = proposal_actions_info.try_borrow_mut_data()?;
+
+    ProposalActions::write_regions(&mut data, account_keys, actions)?;
+
+    let actions_start = 8 + size_of::<ProposalActions>() + 32 * account_keys.len();
+    proposal_account.add_chunk(action_count, account_keys, &data[actions_start..])
+}
+
+/// Checks actions about to be stored in a chunk of `proposal` against `account_keys`,
+/// the chunk's key table.
+pub(crate) fn validate_new_actions(
+    vault_account: &Account<Vault>,
+    program_allowlist: Option<&ProgramAllowlist>,
+    proposal: &Pubkey,
+    account_keys: &[Pubkey],
+    actions: &[Action],
+) -> Result<()> {
+    validate_account_keys(account_keys)?;
+    validate_action_groups(actions.iter().map(|action| Ok(action.view())))?;
+
+    for action in actions.iter() {
+        action
+            .view()
+            .validate_targets(account_keys, &vault_account.key(), Some(proposal))?;
+
+        let view = action.view();
+        let program_id = view.program_id(account_keys)?;
+
+        // Data held in a buffer is checked against the allowlist when it is executed, but
+        // the program must already be allowed.
+        if action.data_buffer.is_some() {
+            ProgramAllowlist::check_program(vault_account, program_allowlist, program_id)?;
+        } else {
+            let pinned_len =
+                ProgramAllowlist::pinned_prefix_len(vault_account, program_allowlist, program_id);
+
+            view.validate_placeholders(account_keys, &action.data, pinned_len)?;
+            ProgramAllowlist::check_target(
+                vault_account,
+                program_allowlist,
+                program_id,
+                &action.data,
+            )?;
+        }
+    }
+
+    Ok(())
+}
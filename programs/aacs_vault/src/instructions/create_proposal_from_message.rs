@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::PROPOSAL_ACTIONS_PREFIX,
+    error::ErrorCode,
+    message::TransactionMessage,
+    state::{
+        EphemeralSignerSeeds, ProgramAllowlist, Proposal, ProposalActions, Vault, VaultSubAccount,
+    },
+    utils::realloc_account,
+};
+
+use super::{init_proposal, validate_new_actions};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateProposalFromMessageParams {
+    pub name: String,
+    /// Serialized legacy or v0 transaction message.
+    pub message: Vec<u8>,
+    /// Opened sub-accounts whose signers sign in the message.
+    pub sub_account_indices: Vec<u8>,
+    pub ephemeral_signer_count: u8,
+    pub co_signers: Vec<Pubkey>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: CreateProposalFromMessageParams)]
+pub struct CreateProposalFromMessage<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init,
+        signer,
+        space = 8 + Proposal::calculate_data_size(
+            &params.name,
+            params.ephemeral_signer_count as usize,
+            params.co_signers.len(),
+            0,
+            0,
+            None,
+            0,
+        ),
+        payer = proposer
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    /// Allocated for the header only, and grown once the message is decompiled.
+    #[account(
+        init,
+        seeds = [PROPOSAL_ACTIONS_PREFIX.as_ref(), proposal.key().as_ref(), 0u16.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + ProposalActions::calculate_data_size(0, 0),
+        payer = proposer
+    )]
+    pub proposal_actions: AccountLoader<'info, ProposalActions>,
+
+    #[account(has_one = vault)]
+    pub program_allowlist: Option<Box<Account<'info, ProgramAllowlist>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates a proposal from the instructions of a transaction message. Address lookup tables
+/// the message references are passed as remaining accounts, along with the
+/// `VaultSubAccount` of every sub-account whose signer signs in the message.
+pub fn create_proposal_from_message_handler(
+    ctx: Context<CreateProposalFromMessage>,
+    params: CreateProposalFromMessageParams,
+) -> Result<()> {
+    let proposal_account = &mut ctx.accounts.proposal;
+    let vault_account = &ctx.accounts.vault;
+
+    let message = TransactionMessage::parse(&params.message)?;
+    let resolved_account_keys = message.resolve_account_keys(ctx.remaining_accounts)?;
+
+    let sub_account_signers = params
+        .sub_account_indices
+        .iter()
+        .map(|index| {
+            VaultSubAccount::find_signer(ctx.remaining_accounts, vault_account.key(), *index)
+                .map(|signer| (*index, signer.address))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let (account_keys, actions) =
+        message.decompile(&resolved_account_keys, &sub_account_signers)?;
+
+    vault_account.proposal_limits.check_name(&params.name)?;
+    vault_account.proposal_limits.check_actions(0, &actions)?;
+
+    validate_new_actions(
+        vault_account,
+        ctx.accounts
+            .program_allowlist
+            .as_deref()
+            .map(|allowlist| &**allowlist),
+        &proposal_account.key(),
+        &account_keys,
+        &actions,
+    )?;
+
+    let actions_size = actions.iter().map(|action| action.get_data_size()).sum();
+    realloc_account(
+        &ctx.accounts.proposal_actions.to_account_info(),
+        &ctx.accounts.proposer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + ProposalActions::calculate_data_size(account_keys.len(), actions_size),
+    )?;
+
+    let ephemeral_signers =
+        EphemeralSignerSeeds::find_all(proposal_account.key(), params.ephemeral_signer_count)?;
+
+    init_proposal(
+        proposal_account,
+        &ctx.accounts.proposal_actions,
+        ctx.bumps.proposal_actions,
+        vault_account.key(),
+        params.name,
+        &ephemeral_signers,
+        params.co_signers,
+        account_keys,
+        actions,
+    )
+}
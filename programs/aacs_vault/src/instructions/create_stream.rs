@@ -0,0 +1,189 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{transfer_checked, Token, TransferChecked},
+    token_interface::{Mint, TokenAccount},
+};
+
+use crate::{
+    constants::{STREAM_PREFIX, VAULT_SIGNER_PREFIX},
+    error::ErrorCode,
+    events::StreamCreated,
+    state::{Stream, Vault},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateStreamParams {
+    pub recipient: Pubkey,
+    pub total_amount: u64,
+    pub start_date: i64,
+    pub cliff_date: Option<i64>,
+    pub end_date: i64,
+}
+
+#[derive(Accounts)]
+pub struct CreateStream<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = &vault.owner == owner.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init,
+        seeds = [
+            STREAM_PREFIX.as_ref(),
+            vault.key().as_ref(),
+            vault.stream_count.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + Stream::INIT_SPACE,
+        payer = owner
+    )]
+    pub stream: Box<Account<'info, Stream>>,
+
+    #[account(
+        mut,
+        seeds = [VAULT_SIGNER_PREFIX.as_ref(), vault.key().as_ref()],
+        bump = vault.signer_bump,
+    )]
+    /// CHECK: vault_signer is a PDA program signer. Data is never read or written to
+    pub vault_signer: UncheckedAccount<'info>,
+
+    /// Streams SOL when omitted.
+    pub token_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = vault_signer,
+    )]
+    pub vault_signer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Escrows the streamed tokens; SOL streams escrow their lamports in the stream itself.
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = token_mint,
+        associated_token::authority = stream,
+        associated_token::token_program = token_program,
+    )]
+    pub stream_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves the whole stream amount out of the vault signer into the stream's escrow, so the
+/// owner can no longer spend funds already promised to the recipient.
+pub fn create_stream_handler(ctx: Context<CreateStream>, params: CreateStreamParams) -> Result<()> {
+    let cliff_date = params.cliff_date.unwrap_or(params.start_date);
+
+    require!(params.total_amount > 0, ErrorCode::InvalidStreamAmount);
+    require!(
+        params.start_date < params.end_date
+            && params.start_date <= cliff_date
+            && cliff_date <= params.end_date,
+        ErrorCode::InvalidStreamSchedule
+    );
+
+    escrow_stream_funds(&ctx, params.total_amount)?;
+
+    let vault_account = &mut ctx.accounts.vault;
+    let stream_account = &mut ctx.accounts.stream;
+
+    stream_account.vault = vault_account.key();
+    stream_account.recipient = params.recipient;
+    stream_account.mint = ctx.accounts.token_mint.as_ref().map(|mint| mint.key());
+    stream_account.stream_id = vault_account.stream_count;
+    stream_account.total_amount = params.total_amount;
+    stream_account.withdrawn_amount = 0;
+    stream_account.start_date = params.start_date;
+    stream_account.cliff_date = cliff_date;
+    stream_account.end_date = params.end_date;
+    stream_account.bump = ctx.bumps.stream;
+
+    vault_account.stream_count += 1;
+
+    emit!(StreamCreated {
+        vault: vault_account.key(),
+        stream: stream_account.key(),
+        recipient: stream_account.recipient,
+        mint: stream_account.mint,
+        total_amount: stream_account.total_amount,
+        start_date: stream_account.start_date,
+        cliff_date: stream_account.cliff_date,
+        end_date: stream_account.end_date,
+    });
+
+    Ok(())
+}
+
+fn escrow_stream_funds(ctx: &Context<CreateStream>, amount: u64) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+
+    let seeds = &[
+        VAULT_SIGNER_PREFIX.as_ref(),
+        vault_key.as_ref(),
+        &[ctx.accounts.vault.signer_bump],
+    ];
+
+    let signer_seeds = &[&seeds[..]];
+
+    let Some(token_mint) = &ctx.accounts.token_mint else {
+        require_gte!(
+            ctx.accounts.vault_signer.lamports(),
+            amount,
+            ErrorCode::NotEnoughLamports
+        );
+
+        let cpi_context = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_signer.to_account_info(),
+                to: ctx.accounts.stream.to_account_info(),
+            },
+            signer_seeds,
+        );
+
+        return transfer(cpi_context, amount);
+    };
+
+    let (Some(vault_signer_token_account), Some(stream_token_account), Some(token_program)) = (
+        &ctx.accounts.vault_signer_token_account,
+        &ctx.accounts.stream_token_account,
+        &ctx.accounts.token_program,
+    ) else {
+        return err!(ErrorCode::StreamMintMismatch);
+    };
+
+    require_gte!(
+        vault_signer_token_account.amount,
+        amount,
+        ErrorCode::NotEnoughTokens
+    );
+
+    let cpi_context = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: vault_signer_token_account.to_account_info(),
+            to: stream_token_account.to_account_info(),
+            authority: ctx.accounts.vault_signer.to_account_info(),
+            mint: token_mint.to_account_info(),
+        },
+        signer_seeds,
+    );
+
+    transfer_checked(cpi_context, amount, token_mint.decimals)
+}
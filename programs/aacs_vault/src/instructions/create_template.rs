@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::DEFAULT_MAX_NAME_LEN,
+    error::ErrorCode,
+    events::ProposalTemplateCreated,
+    state::{validate_action_groups, Action, ProposalTemplate, TemplateKey},
+};
+
+use super::ParamAction;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CreateTemplateParams {
+    pub name: String,
+    pub account_keys: Vec<TemplateKey>,
+    pub actions: Vec<ParamAction>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: CreateTemplateParams)]
+pub struct CreateTemplate<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        signer,
+        space = 8 + ProposalTemplate::calculate_data_size(
+            &params.name,
+            &params.account_keys,
+            params.actions.iter().map(ParamAction::get_data_size).sum(),
+        ),
+        payer = authority
+    )]
+    pub template: Box<Account<'info, ProposalTemplate>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates a template. Its actions are checked against a vault's limits, allowlist and
+/// resolved keys only when the template is instantiated.
+pub fn create_template_handler(
+    ctx: Context<CreateTemplate>,
+    params: CreateTemplateParams,
+) -> Result<()> {
+    require_gte!(
+        DEFAULT_MAX_NAME_LEN as usize,
+        params.name.len(),
+        ErrorCode::NameTooLong
+    );
+    require_gte!(
+        u8::MAX as usize + 1,
+        params.account_keys.len(),
+        ErrorCode::TooManyAccountKeys
+    );
+
+    let actions = params.actions.iter().map(Action::from).collect::<Vec<_>>();
+    let action_count = u16::try_from(actions.len()).map_err(|_| ErrorCode::TooManyActions)?;
+
+    validate_action_groups(actions.iter().map(|action| Ok(action.view())))?;
+
+    let template_account = &mut ctx.accounts.template;
+    template_account.authority = ctx.accounts.authority.key();
+    template_account.name = params.name;
+    template_account.account_keys = params.account_keys;
+    template_account.actions = actions;
+
+    emit!(ProposalTemplateCreated {
+        template: template_account.key(),
+        authority: template_account.authority,
+        name: template_account.name.clone(),
+        action_count,
+    });
+
+    Ok(())
+}
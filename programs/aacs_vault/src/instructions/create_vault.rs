@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
 
-use crate::{events::VaultCreated, state::Vault};
+use crate::{
+    events::VaultCreated,
+    state::{ProposalLimits, Vault},
+};
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct CreateVaultParams {
@@ -30,6 +33,12 @@ pub fn create_vault_handler(ctx: Context<CreateVault>, params: CreateVaultParams
     vault.owner = params.owner;
     vault.signer_bump = params.signer_bump;
     vault.created_date = Clock::get()?.unix_timestamp;
+    vault.stream_count = 0;
+    vault.allowlist_enabled = false;
+    vault.is_executing = false;
+    vault.proposal_limits = ProposalLimits::default();
+    vault.direct_execution_nonce = 0;
+    vault.direct_execution_disabled = false;
 
     emit!(VaultCreated {
         vault: vault.key(),
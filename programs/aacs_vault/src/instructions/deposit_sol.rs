@@ -4,9 +4,8 @@ use anchor_lang::{
 };
 
 use crate::{
-    constants::VAULT_SIGNER_PREFIX, 
     error::ErrorCode, 
-    events::SolDeposited, state::Vault};
+    events::SolDeposited, state::{Vault, VaultSubAccount}};
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct DepositSolParams {
@@ -24,13 +23,15 @@ pub struct DepositSol<'info> {
     )]
     pub vault: Box<Account<'info, Vault>>,
 
+    /// Targets the sub-account's signer instead of the main vault signer when provided.
+    #[account(has_one = vault)]
+    pub sub_account: Option<Box<Account<'info, VaultSubAccount>>>,
+
     #[account(
-        mut, 
-        seeds = [
-            VAULT_SIGNER_PREFIX.as_ref(),
-            vault.key().as_ref()
-        ],
-        bump = vault.signer_bump,
+        mut,
+        address = vault
+            .signer_seeds(vault.key(), sub_account.as_deref().map(|sub_account| &**sub_account))?
+            .address @ErrorCode::InvalidVaultSigner,
     )]
     /// CHECK: this is signer of vault, holds SOL for that
     pub vault_signer: UncheckedAccount<'info>,
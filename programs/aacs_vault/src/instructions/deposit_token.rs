@@ -7,10 +7,9 @@ use anchor_spl::{
 };
 
 use crate::{
-    constants::VAULT_SIGNER_PREFIX, 
     error::ErrorCode, 
     events::TokenDeposited, 
-    state::Vault
+    state::{Vault, VaultSubAccount}
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -29,12 +28,14 @@ pub struct DepositToken<'info> {
     )]
     pub vault: Box<Account<'info, Vault>>,
 
-    #[account( 
-        seeds = [
-            VAULT_SIGNER_PREFIX.as_ref(),
-            vault.key().as_ref()
-        ],
-        bump = vault.signer_bump,
+    /// Targets the sub-account's signer instead of the main vault signer when provided.
+    #[account(has_one = vault)]
+    pub sub_account: Option<Box<Account<'info, VaultSubAccount>>>,
+
+    #[account(
+        address = vault
+            .signer_seeds(vault.key(), sub_account.as_deref().map(|sub_account| &**sub_account))?
+            .address @ErrorCode::InvalidVaultSigner,
     )]
     /// CHECK: this is signer of vault, holds SOL for that
     pub vault_signer: UncheckedAccount<'info>,
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, events::DirectExecutionDisabled, state::Vault};
+
+#[derive(Accounts)]
+pub struct DisableDirectExecution<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = &vault.owner == owner.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution,
+        constraint = !vault.direct_execution_disabled @ErrorCode::DirectExecutionDisabled
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+/// Stops the vault from executing actions outside proposals. This cannot be undone, so a
+/// vault handed over to governed proposals stays that way.
+pub fn disable_direct_execution_handler(ctx: Context<DisableDirectExecution>) -> Result<()> {
+    let vault_account = &mut ctx.accounts.vault;
+
+    vault_account.direct_execution_disabled = true;
+
+    emit!(DirectExecutionDisabled {
+        vault: vault_account.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode,
+    execution::ActionExecutor,
+    state::{
+        validate_account_keys, validate_action_groups, Action, ActionDataBuffer, ProposalActions,
+        ProposalKind,
+    },
+};
+
+use super::{ExecuteProposal, ExecutedActions, ParamAction};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ExecuteCommittedProposalParams {
+    pub account_keys: Vec<Pubkey>,
+    pub actions: Vec<ParamAction>,
+    /// Loads the account keys and actions from buffers instead, leaving the two fields
+    /// above empty.
+    pub buffered_actions: Option<BufferedActions>,
+}
+
+/// Committed actions too large for one transaction, written to `ActionDataBuffer`s attached
+/// to the proposal. The buffers' data, in order, is the account key table followed by the
+/// serialized actions.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BufferedActions {
+    /// Number of buffers, which lead the remaining accounts.
+    pub buffer_count: u8,
+    pub account_key_count: u16,
+}
+
+/// Executes a committed proposal with actions supplied by the caller, which must hash to
+/// the proposal's `actions_hash`. Remaining accounts hold any action buffers, the account
+/// key table, then any co-signers and the `VaultSubAccount` of each sub-account the actions
+/// sign for.
+pub fn execute_committed_proposal_handler(
+    ctx: Context<ExecuteProposal>,
+    params: ExecuteCommittedProposalParams,
+) -> Result<()> {
+    require!(
+        ctx.accounts.proposal.kind == ProposalKind::Committed,
+        ErrorCode::WrongProposalKind
+    );
+
+    let start = ctx.accounts.begin_execution(ctx.remaining_accounts)?;
+
+    let vault_account = &ctx.accounts.vault;
+    let proposal_account = &ctx.accounts.proposal;
+    let proposal_key = proposal_account.key();
+    let vault_key = vault_account.key();
+
+    let buffer_count = params
+        .buffered_actions
+        .as_ref()
+        .map_or(0, |buffered| buffered.buffer_count as usize);
+    require_gte!(
+        ctx.remaining_accounts.len(),
+        buffer_count,
+        ErrorCode::InvalidActionDataBuffer
+    );
+    let (buffer_accounts, key_table_accounts) = ctx.remaining_accounts.split_at(buffer_count);
+
+    let (account_keys, actions) = match &params.buffered_actions {
+        Some(buffered) => {
+            require!(
+                params.account_keys.is_empty() && params.actions.is_empty(),
+                ErrorCode::InvalidActionDataBuffer
+            );
+            read_buffered_actions(
+                buffer_accounts,
+                &proposal_key,
+                buffered.account_key_count as usize,
+                proposal_account.action_count as usize,
+            )?
+        }
+        None => (
+            params.account_keys,
+            params.actions.iter().map(Action::from).collect::<Vec<_>>(),
+        ),
+    };
+
+    let mut actions_bytes = Vec::with_capacity(actions.iter().map(Action::get_data_size).sum());
+    for action in actions.iter() {
+        action.serialize(&mut actions_bytes)?;
+    }
+
+    require_eq!(
+        32 * account_keys.len() + actions_bytes.len(),
+        proposal_account.actions_size as usize,
+        ErrorCode::CommittedActionsSizeMismatch
+    );
+    require_eq!(
+        actions.len(),
+        proposal_account.action_count as usize,
+        ErrorCode::ActionsHashMismatch
+    );
+    require!(
+        ProposalActions::chain_hash(&[0u8; 32], &account_keys, &actions_bytes)
+            == proposal_account.actions_hash,
+        ErrorCode::ActionsHashMismatch
+    );
+
+    // Nothing was validated when the proposal was created, so apply the same checks a
+    // stored proposal goes through.
+    vault_account.proposal_limits.check_actions(0, &actions)?;
+    validate_account_keys(&account_keys)?;
+    validate_action_groups(actions.iter().map(|action| Ok(action.view())))?;
+
+    let key_table_accounts =
+        ActionExecutor::key_table_accounts(key_table_accounts, &account_keys)?;
+
+    let mut executor = ActionExecutor::new(
+        key_table_accounts,
+        vault_key,
+        Some(proposal_key),
+        vault_account.signer_seeds(vault_key, None)?,
+        proposal_account.ephemeral_signer_seeds(proposal_key)?,
+        ctx.remaining_accounts,
+    );
+
+    for (action_index, action) in actions.iter().map(Action::view).enumerate() {
+        executor.validate(
+            action_index as u16,
+            &action,
+            vault_account,
+            ctx.accounts.program_allowlist.as_deref().map(|allowlist| &**allowlist),
+        )?;
+    }
+
+    let mut outcomes = Vec::with_capacity(actions.len());
+    let mut results = Vec::new();
+    executor.execute_all(
+        0,
+        &actions.iter().map(Action::view).collect::<Vec<_>>(),
+        &mut outcomes,
+        &mut results,
+    )?;
+
+    let executed = ExecutedActions {
+        outcomes,
+        results,
+        substitutions: executor.take_substitutions(),
+    };
+
+    ctx.accounts.complete_execution(
+        ctx.remaining_accounts,
+        start,
+        ctx.bumps.execution_result,
+        executed,
+    )
+}
+
+/// Reads `account_key_count` keys and `action_count` actions from the data of `buffers`
+/// taken in order, which must all be attached to `proposal` and hold nothing more.
+fn read_buffered_actions(
+    buffers: &[AccountInfo],
+    proposal: &Pubkey,
+    account_key_count: usize,
+    action_count: usize,
+) -> Result<(Vec<Pubkey>, Vec<Action>)> {
+    let mut payload = Vec::new();
+
+    for buffer in buffers.iter() {
+        let data = buffer.try_borrow_data()?;
+        payload.extend_from_slice(ActionDataBuffer::attached_data(buffer, &data, Some(proposal))?);
+    }
+
+    require_gte!(
+        payload.len(),
+        32 * account_key_count,
+        ErrorCode::CommittedActionsSizeMismatch
+    );
+    let (keys_bytes, mut actions_bytes) = payload.split_at(32 * account_key_count);
+
+    let account_keys = bytemuck::cast_slice::<u8, Pubkey>(keys_bytes).to_vec();
+    let actions = (0..action_count)
+        .map(|_| Action::deserialize(&mut actions_bytes))
+        .collect::<std::io::Result<Vec<_>>>()
+        .map_err(|_| ErrorCode::ActionsHashMismatch)?;
+
+    require!(actions_bytes.is_empty(), ErrorCode::CommittedActionsSizeMismatch);
+
+    Ok((account_keys, actions))
+}
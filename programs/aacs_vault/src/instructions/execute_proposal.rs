@@ -1,18 +1,20 @@
-use std::collections::HashSet;
-
-use anchor_lang::{
-    prelude::*,
-    solana_program::{instruction::Instruction, program::invoke_signed},
-};
+use anchor_lang::prelude::*;
 
 use crate::{
-    constants::VAULT_SIGNER_PREFIX,
+    constants::{EXECUTION_RESULT_PREFIX, VAULT_SIGNER_PREFIX},
     error::ErrorCode,
-    state::{Proposal, ProposalStage, Vault},
+    events::ProposalExecuted,
+    execution::ActionExecutor,
+    state::{
+        ActionOutcome, ActionResult, OutflowSnapshot, PlaceholderSubstitution, ProgramAllowlist, Proposal, ProposalActions,
+        ProposalExecutionResult, ProposalKind, ProposalStage, Vault,
+    },
+    utils::realloc_account,
 };
 
 #[derive(Accounts)]
 pub struct ExecuteProposal<'info> {
+    #[account(mut)]
     pub caller: Signer<'info>,
 
     #[account(
@@ -23,7 +25,9 @@ pub struct ExecuteProposal<'info> {
     pub proposal: Box<Account<'info, Proposal>>,
 
     #[account(
-        constraint = &vault.owner == caller.key @ErrorCode::VaultNotOwned
+        mut,
+        constraint = &vault.owner == caller.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
     )]
     pub vault: Box<Account<'info, Vault>>,
 
@@ -33,79 +37,309 @@ pub struct ExecuteProposal<'info> {
     )]
     /// CHECK: vault_signer is a PDA program signer. Data is never read or written to
     pub vault_signer: UncheckedAccount<'info>,
+
+    #[account(has_one = vault)]
+    pub program_allowlist: Option<Box<Account<'info, ProgramAllowlist>>>,
+
+    #[account(
+        init,
+        seeds = [EXECUTION_RESULT_PREFIX.as_ref(), proposal.key().as_ref()],
+        bump,
+        space = 8 + ProposalExecutionResult::calculate_data_size(&[]),
+        payer = caller
+    )]
+    pub execution_result: Box<Account<'info, ProposalExecutionResult>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// State captured when a proposal starts executing.
+pub(crate) struct ExecutionStart {
+    pub now: i64,
+    /// What each post guard measured before any action ran.
+    post_guard_baselines: Vec<u64>,
+    /// Taken when the vault or the proposal caps outflows.
+    outflow_snapshot: Option<OutflowSnapshot>,
+}
+
+/// What invoking the actions of a proposal produced.
+pub(crate) struct ExecutedActions {
+    pub outcomes: Vec<ActionOutcome>,
+    pub results: Vec<ActionResult>,
+    pub substitutions: Vec<PlaceholderSubstitution>,
+}
+
+/// The accounts the execution of a single proposal updates, shared by the instructions
+/// executing one proposal and several.
+pub(crate) struct ProposalExecution<'a, 'info> {
+    pub proposal: &'a mut Account<'info, Proposal>,
+    pub vault: &'a mut Account<'info, Vault>,
+    pub vault_signer: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl ProposalExecution<'_, '_> {
+    /// Checks the proposal can run, checks its dependencies and pre guards and raises the
+    /// vault's reentrancy guard.
+    pub fn begin(&mut self, remaining_accounts: &[AccountInfo]) -> Result<ExecutionStart> {
+        let now = Clock::get()?.unix_timestamp;
+        require_gte!(self.proposal.expiry_date, now, ErrorCode::ProposalExpired);
+
+        // Persist the guard before invoking anything so a reentrant call observes it.
+        self.vault.is_executing = true;
+        self.vault.exit(&crate::ID)?;
+
+        self.proposal.check_co_signers(remaining_accounts)?;
+        self.proposal.check_dependencies(remaining_accounts)?;
+
+        for (guard_index, guard) in self.proposal.pre_guards.iter().enumerate() {
+            guard.check(guard_index, remaining_accounts, guard.measure(remaining_accounts)?)?;
+        }
+
+        let post_guard_baselines = self
+            .proposal
+            .post_guards
+            .iter()
+            .map(|guard| guard.measure(remaining_accounts))
+            .collect::<Result<Vec<_>>>()?;
+
+        let outflow_snapshot = if !self.vault.outflow_cap.is_empty()
+            || self.proposal.outflow_cap.is_some()
+        {
+            Some(OutflowSnapshot::take(
+                self.vault.key(),
+                self.vault_signer,
+                remaining_accounts,
+            )?)
+        } else {
+            None
+        };
+
+        Ok(ExecutionStart {
+            now,
+            post_guard_baselines,
+            outflow_snapshot,
+        })
+    }
+
+    /// Checks the post guards and outflow caps, marks the proposal executed with the
+    /// outcomes of its actions and lowers the reentrancy guard.
+    pub fn complete(
+        &mut self,
+        remaining_accounts: &[AccountInfo],
+        start: ExecutionStart,
+        executed: ExecutedActions,
+    ) -> Result<()> {
+        for (guard_index, guard) in self.proposal.post_guards.iter().enumerate() {
+            guard.check(
+                guard_index,
+                remaining_accounts,
+                start.post_guard_baselines[guard_index],
+            )?;
+        }
+
+        if let Some(outflow_snapshot) = &start.outflow_snapshot {
+            let outflows = outflow_snapshot.outflows(self.vault_signer, remaining_accounts);
+
+            self.vault.outflow_cap.check(&outflows)?;
+            if let Some(outflow_cap) = &self.proposal.outflow_cap {
+                outflow_cap.check(&outflows)?;
+            }
+        }
+
+        let proposal_key = self.proposal.key();
+        let vault_key = self.vault.key();
+        let proposal_account = &mut self.proposal;
+
+        // Burn the transaction to ensure one time use.
+        proposal_account.is_executed = true;
+        proposal_account.proposal_stage = ProposalStage::Completed;
+
+        realloc_account(
+            &proposal_account.to_account_info(),
+            self.payer,
+            self.system_program,
+            8 + Proposal::calculate_data_size(
+                &proposal_account.name,
+                proposal_account.ephemeral_signer_bumps.len(),
+                proposal_account.co_signers.len(),
+                proposal_account.dependencies.len(),
+                proposal_account.guard_count(),
+                proposal_account.outflow_cap.as_ref(),
+                executed.outcomes.len(),
+            ),
+        )?;
+        proposal_account.action_outcomes = executed.outcomes.clone();
+
+        self.vault.is_executing = false;
+
+        emit!(ProposalExecuted {
+            vault: vault_key,
+            proposal: proposal_key,
+            executed_date: start.now,
+            outcomes: executed.outcomes,
+            results: executed.results,
+            substitutions: executed.substitutions,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> ExecuteProposal<'info> {
+    fn execution(&mut self) -> ProposalExecution<'_, 'info> {
+        ProposalExecution {
+            proposal: &mut self.proposal,
+            vault: &mut self.vault,
+            vault_signer: self.vault_signer.as_ref(),
+            payer: self.caller.as_ref(),
+            system_program: self.system_program.as_ref(),
+        }
+    }
+
+    pub(crate) fn begin_execution(
+        &mut self,
+        remaining_accounts: &[AccountInfo],
+    ) -> Result<ExecutionStart> {
+        self.execution().begin(remaining_accounts)
+    }
+
+    /// Records the return data of the proposal's actions, then completes its execution.
+    pub(crate) fn complete_execution(
+        &mut self,
+        remaining_accounts: &[AccountInfo],
+        start: ExecutionStart,
+        execution_result_bump: u8,
+        executed: ExecutedActions,
+    ) -> Result<()> {
+        let execution_result_account = &mut self.execution_result;
+        let (results, truncated) = ProposalExecutionResult::fit_results(&executed.results);
+
+        realloc_account(
+            &execution_result_account.to_account_info(),
+            &self.caller.to_account_info(),
+            &self.system_program.to_account_info(),
+            8 + ProposalExecutionResult::calculate_data_size(&results),
+        )?;
+
+        execution_result_account.proposal = self.proposal.key();
+        execution_result_account.vault = self.vault.key();
+        execution_result_account.executed_date = start.now;
+        execution_result_account.bump = execution_result_bump;
+        execution_result_account.truncated = truncated;
+        execution_result_account.results = results;
+
+        self.execution().complete(remaining_accounts, start, executed)
+    }
 }
 
 pub fn execute_proposal_handler(ctx: Context<ExecuteProposal>) -> Result<()> {
-    let vault_account = &ctx.accounts.vault;
-    let vault_signer_account = &ctx.accounts.vault_signer;
-    let proposal_account = &mut ctx.accounts.proposal;
-
-    let now = Clock::get()?.unix_timestamp;
-    require_gte!(
-        proposal_account.expiry_date,
-        now,
-        ErrorCode::ProposalExpired
+    require!(
+        ctx.accounts.proposal.kind == ProposalKind::Stored,
+        ErrorCode::WrongProposalKind
     );
 
+    let start = ctx.accounts.begin_execution(ctx.remaining_accounts)?;
+
+    let (executed, _) = execute_stored_actions(
+        &ctx.accounts.proposal,
+        &ctx.accounts.vault,
+        ctx.accounts.program_allowlist.as_deref().map(|allowlist| &**allowlist),
+        ctx.remaining_accounts,
+    )?;
+
+    ctx.accounts.complete_execution(
+        ctx.remaining_accounts,
+        start,
+        ctx.bumps.execution_result,
+        executed,
+    )
+}
+
+/// Validates and invokes the actions of a stored proposal. Remaining accounts hold each
+/// chunk in order, followed by its account key table, and the accounts past the last key
+/// table are returned. None of them signs, so large proposals can load them through
+/// address lookup tables. The `VaultSubAccount` of each sub-account the actions sign for
+/// is looked up among all of `remaining_accounts`. Every chunk and action is checked
+/// before the first action is invoked.
+pub(crate) fn execute_stored_actions<'a, 'info>(
+    proposal_account: &Account<Proposal>,
+    vault_account: &Account<Vault>,
+    program_allowlist: Option<&ProgramAllowlist>,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<(ExecutedActions, &'a [AccountInfo<'info>])> {
+    let proposal_key = proposal_account.key();
     let vault_key = vault_account.key();
-    let seeds = &[
-        VAULT_SIGNER_PREFIX.as_ref(),
-        vault_key.as_ref(),
-        &[ctx.accounts.vault.signer_bump],
-    ];
-    let signer = &[&seeds[..]];
-
-    for action in proposal_account.actions.iter() {
-        let mut unique_pubkeys: HashSet<Pubkey> = HashSet::new();
-        
-        unique_pubkeys.insert(action.program_id);
-
-        let account_metas = action
-            .account_specs
-            .iter()
-            .map(|account_spec| {
-                unique_pubkeys.insert(account_spec.pubkey);
 
-                let mut account_meta = AccountMeta::from(account_spec);
+    let mut executor = ActionExecutor::new(
+        &[],
+        vault_key,
+        Some(proposal_key),
+        vault_account.signer_seeds(vault_key, None)?,
+        proposal_account.ephemeral_signer_seeds(proposal_key)?,
+        remaining_accounts,
+    );
+
+    let mut remaining_accounts = remaining_accounts;
+    let mut actions_hash = [0u8; 32];
+    let mut chunks = Vec::with_capacity(proposal_account.chunk_count as usize);
+    let mut action_index: u16 = 0;
 
-                if &account_meta.pubkey == vault_signer_account.key {
-                    account_meta.is_signer = true;
-                }
+    for chunk_index in 0..proposal_account.chunk_count {
+        let (chunk_info, accounts) = remaining_accounts
+            .split_first()
+            .ok_or(ErrorCode::InvalidProposalActions)?;
 
-                account_meta
-            })
-            .collect::<Vec<_>>();
+        require_keys_eq!(*chunk_info.owner, crate::ID, ErrorCode::InvalidProposalActions);
 
-        let ix = Instruction {
-            accounts: account_metas,
-            data: action.data.clone(),
-            program_id: action.program_id,
-        };
+        // Actions are read in place from the chunk's account data.
+        let data = chunk_info.try_borrow_data()?;
+        let chunk = ProposalActions::split(&data)?;
 
-        let accounts = ctx
-            .remaining_accounts
-            .iter()
-            .filter_map(|account| {
-                if unique_pubkeys.contains(account.key)  {
-                    Some(account.clone())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();      
+        require_keys_eq!(chunk.header.proposal, proposal_key, ErrorCode::InvalidProposalActions);
+        require_eq!(chunk.header.index, chunk_index, ErrorCode::InvalidProposalActions);
+
+        let key_table_accounts = ActionExecutor::key_table_accounts(accounts, chunk.account_keys)?;
+        remaining_accounts = &accounts[key_table_accounts.len()..];
+        actions_hash = ProposalActions::chain_hash(&actions_hash, chunk.account_keys, chunk.actions);
 
-        let result = invoke_signed(&ix, &accounts, signer);
+        executor.set_accounts(key_table_accounts);
 
-        if result.is_err() {
-            proposal_account.proposal_stage = ProposalStage::Failed;
+        for action in chunk.actions() {
+            executor.validate(action_index, &action?, vault_account, program_allowlist)?;
 
-            return Err(result.unwrap_err().into());
+            action_index += 1;
         }
+
+        chunks.push((chunk_info, key_table_accounts));
+    }
+
+    require!(
+        actions_hash == proposal_account.actions_hash,
+        ErrorCode::ActionsHashMismatch
+    );
+
+    let mut outcomes = Vec::with_capacity(proposal_account.action_count as usize);
+    let mut results = Vec::new();
+    let mut action_index: u16 = 0;
+
+    for (chunk_info, key_table_accounts) in chunks {
+        let data = chunk_info.try_borrow_data()?;
+        let chunk = ProposalActions::split(&data)?;
+        let actions = chunk.actions().collect::<Result<Vec<_>>>()?;
+
+        executor.set_accounts(key_table_accounts);
+        executor.execute_all(action_index, &actions, &mut outcomes, &mut results)?;
+
+        action_index += actions.len() as u16;
     }
 
-    // Burn the transaction to ensure one time use.
-    proposal_account.is_executed = true;
-    proposal_account.proposal_stage = ProposalStage::Completed;
+    let executed = ExecutedActions {
+        outcomes,
+        results,
+        substitutions: executor.take_substitutions(),
+    };
 
-    Ok(())
+    Ok((executed, remaining_accounts))
 }
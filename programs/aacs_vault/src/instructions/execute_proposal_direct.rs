@@ -1,21 +1,22 @@
-use std::collections::HashSet;
-
-use anchor_lang::{
-    prelude::*,
-    solana_program::{instruction::Instruction, program::invoke_signed},
-};
+use anchor_lang::prelude::*;
 
 use crate::{
-    constants::VAULT_SIGNER_PREFIX,
+    constants::{DIRECT_EXECUTION_RECORD_PREFIX, VAULT_SIGNER_PREFIX},
     error::ErrorCode,
     events::ProposalDirectExecuted,
-    state::{AccountSpec, Action, Vault},
+    execution::ActionExecutor,
+    state::{
+        validate_action_groups, Action, DirectExecutionRecord, OutflowSnapshot, ProgramAllowlist,
+        ProposalActions, Vault,
+    },
 };
 
 use super::ParamAction;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct ExecuteProposalDirectParams {
+    /// Must equal the vault's `direct_execution_nonce`, so a submission runs at most once.
+    pub nonce: u64,
     pub actions: Vec<ParamAction>,
 }
 
@@ -26,7 +27,11 @@ pub struct ExecuteProposalDirect<'info> {
     proposer: Signer<'info>,
 
     #[account(
-        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned
+        mut,
+        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution,
+        constraint = !vault.direct_execution_disabled @ErrorCode::DirectExecutionDisabled,
+        constraint = vault.direct_execution_nonce == params.nonce @ErrorCode::InvalidDirectExecutionNonce
     )]
     vault: Box<Account<'info, Vault>>,
 
@@ -37,6 +42,22 @@ pub struct ExecuteProposalDirect<'info> {
     /// CHECK: vault_signer is a PDA program signer. Data is never read or written to
     vault_signer: UncheckedAccount<'info>,
 
+    #[account(has_one = vault)]
+    program_allowlist: Option<Box<Account<'info, ProgramAllowlist>>>,
+
+    #[account(
+        init,
+        seeds = [
+            DIRECT_EXECUTION_RECORD_PREFIX.as_ref(),
+            vault.key().as_ref(),
+            params.nonce.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + DirectExecutionRecord::INIT_SPACE,
+        payer = proposer
+    )]
+    direct_execution_record: Box<Account<'info, DirectExecutionRecord>>,
+
     system_program: Program<'info, System>,
 }
 
@@ -44,79 +65,89 @@ pub fn execute_proposal_direct_handler(
     ctx: Context<ExecuteProposalDirect>,
     params: ExecuteProposalDirectParams,
 ) -> Result<()> {
-    let vault_account = &ctx.accounts.vault;
-    let vault_signer_account = &ctx.accounts.vault_signer;
+    // Persist the guard before invoking anything so a reentrant call observes it.
+    ctx.accounts.vault.is_executing = true;
+    ctx.accounts.vault.exit(&crate::ID)?;
 
+    let vault_account = &ctx.accounts.vault;
     let vault_key = vault_account.key();
 
-    let seeds = &[
-        VAULT_SIGNER_PREFIX.as_ref(),
-        vault_key.as_ref(),
-        &[ctx.accounts.vault.signer_bump],
-    ];
-
-    let signer = &[&seeds[..]];
-
-    let actions = params
-        .actions
-        .iter()
-        .map(|acc| {
-            let account_specs = acc
-                .account_specs
-                .iter()
-                .map(AccountSpec::from)
-                .collect::<Vec<_>>();
+    let actions = params.actions.iter().map(Action::from).collect::<Vec<_>>();
+
+    vault_account.proposal_limits.check_actions(0, &actions)?;
+    validate_action_groups(actions.iter().map(|action| Ok(action.view())))?;
+
+    // Direct execution indexes straight into the remaining accounts.
+    let mut executor = ActionExecutor::new(
+        ctx.remaining_accounts,
+        vault_key,
+        None,
+        vault_account.signer_seeds(vault_key, None)?,
+        vec![],
+        ctx.remaining_accounts,
+    );
+
+    for (action_index, action) in actions.iter().map(Action::view).enumerate() {
+        executor.validate(
+            action_index as u16,
+            &action,
+            vault_account,
+            ctx.accounts.program_allowlist.as_deref().map(|allowlist| &**allowlist),
+        )?;
+    }
 
-            Action::new(acc.program_id, account_specs, acc.data.clone())
-        })
-        .collect::<Vec<_>>();
+    let outflow_snapshot = if !vault_account.outflow_cap.is_empty() {
+        Some(OutflowSnapshot::take(
+            vault_key,
+            ctx.accounts.vault_signer.as_ref(),
+            ctx.remaining_accounts,
+        )?)
+    } else {
+        None
+    };
+
+    let mut outcomes = Vec::with_capacity(actions.len());
+    executor.execute_all(
+        0,
+        &actions.iter().map(Action::view).collect::<Vec<_>>(),
+        &mut outcomes,
+        &mut vec![],
+    )?;
+
+    if let Some(outflow_snapshot) = &outflow_snapshot {
+        vault_account.outflow_cap.check(
+            &outflow_snapshot.outflows(ctx.accounts.vault_signer.as_ref(), ctx.remaining_accounts),
+        )?;
+    }
 
+    let mut actions_bytes = Vec::with_capacity(actions.iter().map(Action::get_data_size).sum());
     for action in actions.iter() {
-        let mut unique_pubkeys: HashSet<Pubkey> = HashSet::new();
-
-        unique_pubkeys.insert(action.program_id);
-
-        let account_metas = action
-            .account_specs
-            .iter()
-            .map(|account_spec| {
-                unique_pubkeys.insert(account_spec.pubkey);
-
-                let mut account_meta = AccountMeta::from(account_spec);
-
-                if &account_meta.pubkey == vault_signer_account.key {
-                    account_meta.is_signer = true;
-                }
-
-                account_meta
-            })
-            .collect::<Vec<_>>();
-
-        let ix = Instruction {
-            accounts: account_metas,
-            data: action.data.clone(),
-            program_id: action.program_id,
-        };
-
-        let accounts = ctx
-            .remaining_accounts
-            .iter()
-            .filter_map(|account| {
-                if unique_pubkeys.contains(account.key) {
-                    Some(account.clone())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-
-        invoke_signed(&ix, &accounts, signer)?;
+        action.serialize(&mut actions_bytes)?;
     }
+    let actions_hash =
+        ProposalActions::chain_hash(&[0u8; 32], executor.account_keys(), &actions_bytes);
+
+    let record_account = &mut ctx.accounts.direct_execution_record;
+    record_account.vault = vault_key;
+    record_account.caller = ctx.accounts.proposer.key();
+    record_account.nonce = params.nonce;
+    record_account.actions_hash = actions_hash;
+    record_account.executed_date = Clock::get()?.unix_timestamp;
+    record_account.bump = ctx.bumps.direct_execution_record;
+
+    ctx.accounts.vault.direct_execution_nonce += 1;
+    ctx.accounts.vault.is_executing = false;
 
     emit!(ProposalDirectExecuted {
+        account_keys: executor.account_keys().to_vec(),
         actions,
-        created_date: Clock::get()?.unix_timestamp,
-        vault: vault_account.key()
+        outcomes,
+        substitutions: executor.take_substitutions(),
+        created_date: record_account.executed_date,
+        nonce: params.nonce,
+        record: record_account.key(),
+        actions_hash,
+        vault: vault_key
     });
 
     Ok(())
@@ -0,0 +1,169 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{EXECUTION_RESULT_PREFIX, VAULT_SIGNER_PREFIX},
+    error::ErrorCode,
+    events::ProposalBatchExecuted,
+    state::{
+        OutflowSnapshot, ProgramAllowlist, Proposal, ProposalExecutionResult, ProposalKind,
+        Vault,
+    },
+    utils::create_pda_account,
+};
+
+use super::{execute_stored_actions, ProposalExecution};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ExecuteProposalsParams {
+    pub proposal_count: u8,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposals<'info> {
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = &vault.owner == caller.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        seeds = [VAULT_SIGNER_PREFIX.as_ref(), vault.key().as_ref()],
+        bump = vault.signer_bump,
+    )]
+    /// CHECK: vault_signer is a PDA program signer. Data is never read or written to
+    pub vault_signer: UncheckedAccount<'info>,
+
+    #[account(has_one = vault)]
+    pub program_allowlist: Option<Box<Account<'info, ProgramAllowlist>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Executes stored proposals of the vault in order, all in one transaction, so either every
+/// proposal completes or none does. For each proposal the remaining accounts hold the
+/// proposal, its uninitialized execution result PDA, then its chunks and key tables as
+/// `execute_proposal` takes them. Co-signers, guarded accounts and dependencies of any
+/// proposal follow the last proposal. A proposal may depend on one executed before it in
+/// the same batch. The vault's outflow cap bounds each proposal and the batch as a whole.
+pub fn execute_proposals_handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ExecuteProposals<'info>>,
+    params: ExecuteProposalsParams,
+) -> Result<()> {
+    require_gt!(params.proposal_count, 0, ErrorCode::InvalidProposalBatch);
+
+    let accounts = ctx.accounts;
+    let program_allowlist = accounts
+        .program_allowlist
+        .as_deref()
+        .map(|allowlist| &**allowlist);
+    let vault_key = accounts.vault.key();
+
+    let batch_snapshot = if accounts.vault.outflow_cap.is_empty() {
+        None
+    } else {
+        Some(OutflowSnapshot::take(
+            vault_key,
+            accounts.vault_signer.as_ref(),
+            ctx.remaining_accounts,
+        )?)
+    };
+
+    let mut remaining_accounts = ctx.remaining_accounts;
+    let mut proposals = Vec::with_capacity(params.proposal_count as usize);
+
+    for _ in 0..params.proposal_count {
+        let [proposal_info, execution_result_info, rest @ ..] = remaining_accounts else {
+            return err!(ErrorCode::InvalidProposalBatch);
+        };
+
+        // Loaded only now, so proposals executed earlier in the batch are seen completed.
+        let mut proposal_account = Account::<Proposal>::try_from(proposal_info)?;
+        let proposal_key = proposal_account.key();
+
+        require_keys_eq!(proposal_account.vault, vault_key, ErrorCode::ProposalNotOwned);
+        require!(
+            !proposal_account.is_executed,
+            ErrorCode::ProposalAlreadyExecuted
+        );
+        require!(
+            proposal_account.kind == ProposalKind::Stored,
+            ErrorCode::WrongProposalKind
+        );
+
+        let mut execution = ProposalExecution {
+            proposal: &mut proposal_account,
+            vault: &mut accounts.vault,
+            vault_signer: accounts.vault_signer.as_ref(),
+            payer: accounts.caller.as_ref(),
+            system_program: accounts.system_program.as_ref(),
+        };
+
+        let start = execution.begin(ctx.remaining_accounts)?;
+
+        let (executed, rest) = execute_stored_actions(
+            execution.proposal,
+            execution.vault,
+            program_allowlist,
+            rest,
+        )?;
+
+        let (execution_result_key, execution_result_bump) = Pubkey::find_program_address(
+            &[EXECUTION_RESULT_PREFIX.as_ref(), proposal_key.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            *execution_result_info.key,
+            execution_result_key,
+            ErrorCode::InvalidProposalBatch
+        );
+
+        let (results, truncated) = ProposalExecutionResult::fit_results(&executed.results);
+        let execution_result = ProposalExecutionResult {
+            proposal: proposal_key,
+            vault: vault_key,
+            executed_date: start.now,
+            bump: execution_result_bump,
+            truncated,
+            results,
+        };
+
+        create_pda_account(
+            execution_result_info,
+            execution.payer,
+            execution.system_program,
+            8 + ProposalExecutionResult::calculate_data_size(&execution_result.results),
+            &[
+                EXECUTION_RESULT_PREFIX.as_ref(),
+                proposal_key.as_ref(),
+                &[execution_result_bump],
+            ],
+        )?;
+        execution_result.try_serialize(&mut &mut execution_result_info.try_borrow_mut_data()?[..])?;
+
+        execution.complete(ctx.remaining_accounts, start, executed)?;
+
+        // Persist now, as the proposal is not one of the instruction's accounts.
+        proposal_account.exit(&crate::ID)?;
+
+        proposals.push(proposal_key);
+        remaining_accounts = rest;
+    }
+
+    if let Some(batch_snapshot) = &batch_snapshot {
+        let outflows =
+            batch_snapshot.outflows(accounts.vault_signer.as_ref(), ctx.remaining_accounts);
+        accounts.vault.outflow_cap.check(&outflows)?;
+    }
+
+    emit!(ProposalBatchExecuted {
+        vault: vault_key,
+        proposals,
+        executed_date: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::PROPOSAL_ACTIONS_PREFIX,
+    error::ErrorCode,
+    events::ProposalTemplateInstantiated,
+    state::{
+        EphemeralSignerSeeds, ProgramAllowlist, Proposal, ProposalActions, ProposalTemplate, Vault,
+    },
+};
+
+use super::{init_proposal, validate_new_actions};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InstantiateTemplateParams {
+    pub name: String,
+    pub ephemeral_signer_count: u8,
+    pub co_signers: Vec<Pubkey>,
+}
+
+#[derive(Accounts)]
+#[instruction(params: InstantiateTemplateParams)]
+pub struct InstantiateTemplate<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    pub template: Box<Account<'info, ProposalTemplate>>,
+
+    #[account(
+        init,
+        signer,
+        space = 8 + Proposal::calculate_data_size(
+            &params.name,
+            params.ephemeral_signer_count as usize,
+            params.co_signers.len(),
+            0,
+            0,
+            None,
+            0,
+        ),
+        payer = proposer
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    #[account(
+        init,
+        seeds = [PROPOSAL_ACTIONS_PREFIX.as_ref(), proposal.key().as_ref(), 0u16.to_le_bytes().as_ref()],
+        bump,
+        space = 8 + template.chunk_data_size(),
+        payer = proposer
+    )]
+    pub proposal_actions: AccountLoader<'info, ProposalActions>,
+
+    #[account(has_one = vault)]
+    pub program_allowlist: Option<Box<Account<'info, ProgramAllowlist>>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates a proposal from a template, resolving its symbolic account keys against the
+/// vault and the new proposal.
+pub fn instantiate_template_handler(
+    ctx: Context<InstantiateTemplate>,
+    params: InstantiateTemplateParams,
+) -> Result<()> {
+    let proposal_account = &mut ctx.accounts.proposal;
+    let vault_account = &ctx.accounts.vault;
+    let template_account = &ctx.accounts.template;
+    let actions = &template_account.actions;
+
+    vault_account.proposal_limits.check_name(&params.name)?;
+    vault_account.proposal_limits.check_actions(0, actions)?;
+
+    let ephemeral_signers =
+        EphemeralSignerSeeds::find_all(proposal_account.key(), params.ephemeral_signer_count)?;
+
+    let account_keys = template_account.resolve_account_keys(
+        &vault_account.key(),
+        &vault_account.signer_seeds(vault_account.key(), None)?.address,
+        &proposal_account.key(),
+        &ephemeral_signers,
+    )?;
+
+    validate_new_actions(
+        vault_account,
+        ctx.accounts
+            .program_allowlist
+            .as_deref()
+            .map(|allowlist| &**allowlist),
+        &proposal_account.key(),
+        &account_keys,
+        actions,
+    )?;
+
+    init_proposal(
+        proposal_account,
+        &ctx.accounts.proposal_actions,
+        ctx.bumps.proposal_actions,
+        vault_account.key(),
+        params.name,
+        &ephemeral_signers,
+        params.co_signers,
+        account_keys,
+        actions.clone(),
+    )?;
+
+    emit!(ProposalTemplateInstantiated {
+        template: template_account.key(),
+        proposal: proposal_account.key(),
+        vault: vault_account.key(),
+    });
+
+    Ok(())
+}
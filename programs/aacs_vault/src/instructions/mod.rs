@@ -1,19 +1,65 @@
 mod append_actions;
+mod attach_buffer;
+mod cancel_stream;
+mod close_buffer;
+mod close_template;
+mod create_buffer;
+mod create_committed_proposal;
 mod create_proposal;
+mod create_proposal_from_message;
+mod create_stream;
+mod create_template;
 mod create_vault;
 mod deposit_sol;
 mod deposit_token;
+mod disable_direct_execution;
+mod execute_committed_proposal;
 mod execute_proposal;
 mod execute_proposal_direct;
+mod execute_proposals;
+mod instantiate_template;
+mod open_sub_account;
+mod remove_actions;
+mod set_program_allowlist;
+mod set_proposal_dependencies;
+mod set_proposal_guards;
+mod set_proposal_limits;
+mod set_proposal_outflow_cap;
+mod set_vault_outflow_cap;
+mod withdraw_from_stream;
 mod withdraw_sol;
 mod withdraw_token;
+mod write_buffer;
 
 pub use append_actions::*;
+pub use attach_buffer::*;
+pub use cancel_stream::*;
+pub use close_buffer::*;
+pub use close_template::*;
+pub use create_buffer::*;
+pub use create_committed_proposal::*;
 pub use create_proposal::*;
+pub use create_proposal_from_message::*;
+pub use create_stream::*;
+pub use create_template::*;
 pub use create_vault::*;
 pub use deposit_sol::*;
 pub use deposit_token::*;
+pub use disable_direct_execution::*;
+pub use execute_committed_proposal::*;
 pub use execute_proposal::*;
 pub use execute_proposal_direct::*;
+pub use execute_proposals::*;
+pub use instantiate_template::*;
+pub use open_sub_account::*;
+pub use remove_actions::*;
+pub use set_program_allowlist::*;
+pub use set_proposal_dependencies::*;
+pub use set_proposal_guards::*;
+pub use set_proposal_limits::*;
+pub use set_proposal_outflow_cap::*;
+pub use set_vault_outflow_cap::*;
+pub use withdraw_from_stream::*;
 pub use withdraw_sol::*;
 pub use withdraw_token::*;
+pub use write_buffer::*;
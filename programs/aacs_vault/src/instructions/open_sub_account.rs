@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::SUB_ACCOUNT_PREFIX,
+    error::ErrorCode,
+    events::SubAccountOpened,
+    state::{Vault, VaultSignerSeeds, VaultSubAccount},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct OpenSubAccountParams {
+    pub index: u8,
+}
+
+#[derive(Accounts)]
+#[instruction(params: OpenSubAccountParams)]
+pub struct OpenSubAccount<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        constraint = &vault.owner == owner.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init,
+        seeds = [
+            SUB_ACCOUNT_PREFIX.as_ref(),
+            vault.key().as_ref(),
+            &[params.index]
+        ],
+        bump,
+        space = 8 + VaultSubAccount::INIT_SPACE,
+        payer = owner
+    )]
+    pub sub_account: Box<Account<'info, VaultSubAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn open_sub_account_handler(
+    ctx: Context<OpenSubAccount>,
+    params: OpenSubAccountParams,
+) -> Result<()> {
+    let vault_account = &ctx.accounts.vault;
+    let sub_account = &mut ctx.accounts.sub_account;
+
+    let signer_seeds = VaultSignerSeeds::find_sub_account(vault_account.key(), params.index)?;
+
+    sub_account.vault = vault_account.key();
+    sub_account.index = params.index;
+    sub_account.signer_bump = signer_seeds.bump();
+    sub_account.created_date = Clock::get()?.unix_timestamp;
+
+    emit!(SubAccountOpened {
+        vault: vault_account.key(),
+        sub_account: sub_account.key(),
+        vault_signer: signer_seeds.address,
+        index: params.index,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::PROPOSAL_ACTIONS_PREFIX,
+    error::ErrorCode,
+    events::ActionsRemoved,
+    state::{Proposal, ProposalActions, ProposalKind, Vault},
+};
+
+#[derive(Accounts)]
+pub struct RemoveActions<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        has_one = vault @ErrorCode::ProposalNotOwned,
+        constraint = !proposal.is_executed @ErrorCode::ProposalAlreadyExecuted,
+        constraint = proposal.kind == ProposalKind::Stored @ErrorCode::WrongProposalKind,
+        constraint = proposal.chunk_count > 0 @ErrorCode::NoActionsToRemove,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    /// The proposal's last chunk, closed to the proposer.
+    #[account(
+        mut,
+        seeds = [
+            PROPOSAL_ACTIONS_PREFIX.as_ref(),
+            proposal.key().as_ref(),
+            (proposal.chunk_count - 1).to_le_bytes().as_ref()
+        ],
+        bump = proposal_actions.load()?.bump,
+        close = proposer
+    )]
+    pub proposal_actions: AccountLoader<'info, ProposalActions>,
+}
+
+pub fn remove_actions_handler(ctx: Context<RemoveActions>) -> Result<()> {
+    let proposal_account = &mut ctx.accounts.proposal;
+    let proposal_actions_info = ctx.accounts.proposal_actions.to_account_info();
+    let data = proposal_actions_info.try_borrow_data()?;
+    let chunk = ProposalActions::split(&data)?;
+
+    proposal_account.remove_last_chunk(&chunk)?;
+
+    emit!(ActionsRemoved {
+        proposal: proposal_account.key(),
+        chunk_index: chunk.header.index,
+        action_count: chunk.header.action_count,
+    });
+
+    Ok(())
+}
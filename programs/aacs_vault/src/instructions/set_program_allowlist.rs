@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::PROGRAM_ALLOWLIST_PREFIX,
+    error::ErrorCode,
+    events::ProgramAllowlistUpdated,
+    state::{AllowedProgram, ProgramAllowlist, Vault},
+    utils::realloc_account,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ParamAllowedProgram {
+    pub program_id: Pubkey,
+    pub discriminators: Vec<Vec<u8>>,
+}
+
+impl From<&ParamAllowedProgram> for AllowedProgram {
+    fn from(allowed_program: &ParamAllowedProgram) -> Self {
+        AllowedProgram {
+            program_id: allowed_program.program_id,
+            discriminators: allowed_program.discriminators.clone(),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetProgramAllowlistParams {
+    pub enabled: bool,
+    pub programs: Vec<ParamAllowedProgram>,
+}
+
+#[derive(Accounts)]
+pub struct SetProgramAllowlist<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = &vault.owner == owner.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init_if_needed,
+        seeds = [PROGRAM_ALLOWLIST_PREFIX.as_ref(), vault.key().as_ref()],
+        bump,
+        space = 8 + ProgramAllowlist::calculate_data_size(&[]),
+        payer = owner
+    )]
+    pub program_allowlist: Box<Account<'info, ProgramAllowlist>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_program_allowlist_handler(
+    ctx: Context<SetProgramAllowlist>,
+    params: SetProgramAllowlistParams,
+) -> Result<()> {
+    let vault_account = &mut ctx.accounts.vault;
+    let program_allowlist_account = &mut ctx.accounts.program_allowlist;
+
+    let programs = params
+        .programs
+        .iter()
+        .map(AllowedProgram::from)
+        .collect::<Vec<_>>();
+
+    realloc_account(
+        &program_allowlist_account.to_account_info(),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + ProgramAllowlist::calculate_data_size(&programs),
+    )?;
+
+    program_allowlist_account.vault = vault_account.key();
+    program_allowlist_account.bump = ctx.bumps.program_allowlist;
+    program_allowlist_account.programs = programs.clone();
+
+    vault_account.allowlist_enabled = params.enabled;
+
+    emit!(ProgramAllowlistUpdated {
+        vault: vault_account.key(),
+        enabled: params.enabled,
+        programs,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::MAX_DEPENDENCIES,
+    error::ErrorCode,
+    events::ProposalDependenciesUpdated,
+    state::{Proposal, Vault},
+    utils::realloc_account,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetProposalDependenciesParams {
+    pub dependencies: Vec<Pubkey>,
+}
+
+#[derive(Accounts)]
+pub struct SetProposalDependencies<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        has_one = vault @ErrorCode::ProposalNotOwned,
+        constraint = !proposal.is_executed @ErrorCode::ProposalAlreadyExecuted,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Replaces the proposals that must be completed before the proposal executes. Each
+/// dependency is passed as a remaining account so it can be checked to belong to the vault.
+pub fn set_proposal_dependencies_handler(
+    ctx: Context<SetProposalDependencies>,
+    params: SetProposalDependenciesParams,
+) -> Result<()> {
+    let proposal_account = &mut ctx.accounts.proposal;
+    let proposal_key = proposal_account.key();
+    let vault_key = ctx.accounts.vault.key();
+
+    require_gte!(
+        MAX_DEPENDENCIES,
+        params.dependencies.len(),
+        ErrorCode::TooManyDependencies
+    );
+
+    for (index, dependency) in params.dependencies.iter().enumerate() {
+        require_keys_neq!(*dependency, proposal_key, ErrorCode::InvalidDependency);
+        require!(
+            !params.dependencies[..index].contains(dependency),
+            ErrorCode::InvalidDependency
+        );
+
+        Proposal::load_dependency(ctx.remaining_accounts, dependency, &vault_key)?;
+    }
+
+    realloc_account(
+        &proposal_account.to_account_info(),
+        &ctx.accounts.proposer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + Proposal::calculate_data_size(
+            &proposal_account.name,
+            proposal_account.ephemeral_signer_bumps.len(),
+            proposal_account.co_signers.len(),
+            params.dependencies.len(),
+            proposal_account.guard_count(),
+            proposal_account.outflow_cap.as_ref(),
+            0,
+        ),
+    )?;
+
+    proposal_account.dependencies = params.dependencies;
+
+    emit!(ProposalDependenciesUpdated {
+        proposal: proposal_key,
+        dependencies: proposal_account.dependencies.clone(),
+    });
+
+    Ok(())
+}
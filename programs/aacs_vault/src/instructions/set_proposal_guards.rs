@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::MAX_GUARDS,
+    error::ErrorCode,
+    events::ProposalGuardsUpdated,
+    state::{Guard, Proposal, Vault},
+    utils::realloc_account,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetProposalGuardsParams {
+    pub pre_guards: Vec<Guard>,
+    pub post_guards: Vec<Guard>,
+}
+
+#[derive(Accounts)]
+pub struct SetProposalGuards<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        has_one = vault @ErrorCode::ProposalNotOwned,
+        constraint = !proposal.is_executed @ErrorCode::ProposalAlreadyExecuted,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Replaces the guards checked around the proposal's execution.
+pub fn set_proposal_guards_handler(
+    ctx: Context<SetProposalGuards>,
+    params: SetProposalGuardsParams,
+) -> Result<()> {
+    let proposal_account = &mut ctx.accounts.proposal;
+
+    require_gte!(
+        MAX_GUARDS,
+        params.pre_guards.len() + params.post_guards.len(),
+        ErrorCode::TooManyGuards
+    );
+    require!(
+        !params.pre_guards.iter().any(Guard::is_relative),
+        ErrorCode::InvalidGuard
+    );
+
+    realloc_account(
+        &proposal_account.to_account_info(),
+        &ctx.accounts.proposer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + Proposal::calculate_data_size(
+            &proposal_account.name,
+            proposal_account.ephemeral_signer_bumps.len(),
+            proposal_account.co_signers.len(),
+            proposal_account.dependencies.len(),
+            params.pre_guards.len() + params.post_guards.len(),
+            proposal_account.outflow_cap.as_ref(),
+            0,
+        ),
+    )?;
+
+    proposal_account.pre_guards = params.pre_guards;
+    proposal_account.post_guards = params.post_guards;
+
+    emit!(ProposalGuardsUpdated {
+        proposal: proposal_account.key(),
+        pre_guards: proposal_account.pre_guards.clone(),
+        post_guards: proposal_account.post_guards.clone(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode,
+    events::ProposalLimitsUpdated,
+    state::{ProposalLimits, Vault},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetProposalLimitsParams {
+    pub max_name_len: u16,
+    pub max_actions: u16,
+    pub max_action_data_len: u32,
+    pub max_account_specs: u16,
+}
+
+#[derive(Accounts)]
+pub struct SetProposalLimits<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = &vault.owner == owner.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn set_proposal_limits_handler(
+    ctx: Context<SetProposalLimits>,
+    params: SetProposalLimitsParams,
+) -> Result<()> {
+    let vault_account = &mut ctx.accounts.vault;
+
+    vault_account.proposal_limits = ProposalLimits {
+        max_name_len: params.max_name_len,
+        max_actions: params.max_actions,
+        max_action_data_len: params.max_action_data_len,
+        max_account_specs: params.max_account_specs,
+    };
+
+    emit!(ProposalLimitsUpdated {
+        vault: vault_account.key(),
+        proposal_limits: vault_account.proposal_limits,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode,
+    events::OutflowCapUpdated,
+    state::{OutflowCap, Proposal, Vault},
+    utils::realloc_account,
+};
+
+use super::SetOutflowCapParams;
+
+#[derive(Accounts)]
+pub struct SetProposalOutflowCap<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        constraint = &vault.owner == proposer.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        has_one = vault @ErrorCode::ProposalNotOwned,
+        constraint = !proposal.is_executed @ErrorCode::ProposalAlreadyExecuted,
+    )]
+    pub proposal: Box<Account<'info, Proposal>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Sets the proposal's own outflow cap, or removes it when `params` caps nothing.
+pub fn set_proposal_outflow_cap_handler(
+    ctx: Context<SetProposalOutflowCap>,
+    params: SetOutflowCapParams,
+) -> Result<()> {
+    let proposal_account = &mut ctx.accounts.proposal;
+
+    let outflow_cap = OutflowCap::from(params);
+    outflow_cap.validate()?;
+
+    let outflow_cap = (!outflow_cap.is_empty()).then_some(outflow_cap);
+
+    realloc_account(
+        &proposal_account.to_account_info(),
+        &ctx.accounts.proposer.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        8 + Proposal::calculate_data_size(
+            &proposal_account.name,
+            proposal_account.ephemeral_signer_bumps.len(),
+            proposal_account.co_signers.len(),
+            proposal_account.dependencies.len(),
+            proposal_account.guard_count(),
+            outflow_cap.as_ref(),
+            0,
+        ),
+    )?;
+
+    proposal_account.outflow_cap = outflow_cap;
+
+    emit!(OutflowCapUpdated {
+        vault: ctx.accounts.vault.key(),
+        proposal: Some(proposal_account.key()),
+        outflow_cap: proposal_account.outflow_cap.clone(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ErrorCode,
+    events::OutflowCapUpdated,
+    state::{OutflowCap, TokenOutflowCap, Vault},
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetOutflowCapParams {
+    pub max_lamports: Option<u64>,
+    pub token_caps: Vec<TokenOutflowCap>,
+}
+
+impl From<SetOutflowCapParams> for OutflowCap {
+    fn from(params: SetOutflowCapParams) -> Self {
+        OutflowCap {
+            max_lamports: params.max_lamports,
+            token_caps: params.token_caps,
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetVaultOutflowCap<'info> {
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = &vault.owner == owner.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn set_vault_outflow_cap_handler(
+    ctx: Context<SetVaultOutflowCap>,
+    params: SetOutflowCapParams,
+) -> Result<()> {
+    let vault_account = &mut ctx.accounts.vault;
+
+    let outflow_cap = OutflowCap::from(params);
+    outflow_cap.validate()?;
+
+    vault_account.outflow_cap = outflow_cap;
+
+    emit!(OutflowCapUpdated {
+        vault: vault_account.key(),
+        proposal: None,
+        outflow_cap: Some(vault_account.outflow_cap.clone()),
+    });
+
+    Ok(())
+}
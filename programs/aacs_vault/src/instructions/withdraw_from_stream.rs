@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::{
+    token::{transfer_checked, Token, TransferChecked},
+    token_interface::{Mint, TokenAccount},
+};
+
+use crate::{
+    constants::STREAM_PREFIX,
+    error::ErrorCode,
+    events::StreamWithdrawn,
+    state::{Stream, Vault},
+};
+
+#[derive(Accounts)]
+pub struct WithdrawFromStream<'info> {
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    #[account(
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        has_one = vault,
+        has_one = recipient,
+        seeds = [
+            STREAM_PREFIX.as_ref(),
+            vault.key().as_ref(),
+            stream.stream_id.to_le_bytes().as_ref()
+        ],
+        bump = stream.bump,
+    )]
+    pub stream: Box<Account<'info, Stream>>,
+
+    pub token_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = stream,
+    )]
+    pub stream_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = recipient,
+    )]
+    pub recipient_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+pub fn withdraw_from_stream_handler(ctx: Context<WithdrawFromStream>) -> Result<()> {
+    let vault_account = &ctx.accounts.vault;
+    let stream_account = &mut ctx.accounts.stream;
+
+    let now = Clock::get()?.unix_timestamp;
+    let amount = stream_account.withdrawable_amount(now);
+
+    require!(amount > 0, ErrorCode::NothingToWithdraw);
+
+    let recipient_token_account = ctx
+        .accounts
+        .recipient_token_account
+        .as_ref()
+        .map(|account| account.to_account_info());
+
+    pay_out_stream(
+        stream_account,
+        &ctx.accounts.recipient.to_account_info(),
+        &ctx.accounts.token_mint,
+        &ctx.accounts.stream_token_account,
+        recipient_token_account.as_ref(),
+        &ctx.accounts.token_program,
+        amount,
+    )?;
+
+    stream_account.withdrawn_amount += amount;
+
+    emit!(StreamWithdrawn {
+        vault: vault_account.key(),
+        stream: stream_account.key(),
+        recipient: stream_account.recipient,
+        amount,
+        withdrawn_amount: stream_account.withdrawn_amount,
+    });
+
+    Ok(())
+}
+
+/// Transfers `amount` of the stream's asset out of its escrow to `to_account`, signing with
+/// the stream PDA. Token streams require all token accounts.
+pub(crate) fn pay_out_stream<'info>(
+    stream_account: &Account<'info, Stream>,
+    to_account: &AccountInfo<'info>,
+    token_mint: &Option<Box<InterfaceAccount<'info, Mint>>>,
+    stream_token_account: &Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    to_token_account: Option<&AccountInfo<'info>>,
+    token_program: &Option<Program<'info, Token>>,
+    amount: u64,
+) -> Result<()> {
+    let Some(mint) = stream_account.mint else {
+        // The stream account is program owned, so its escrowed lamports move directly.
+        stream_account.sub_lamports(amount)?;
+        to_account.add_lamports(amount)?;
+        return Ok(());
+    };
+
+    let (Some(token_mint), Some(stream_token_account), Some(to_token_account), Some(token_program)) =
+        (token_mint, stream_token_account, to_token_account, token_program)
+    else {
+        return err!(ErrorCode::StreamMintMismatch);
+    };
+
+    require_keys_eq!(token_mint.key(), mint, ErrorCode::StreamMintMismatch);
+
+    let vault_key = stream_account.vault;
+    let stream_id = stream_account.stream_id.to_le_bytes();
+
+    let seeds = &[
+        STREAM_PREFIX.as_ref(),
+        vault_key.as_ref(),
+        stream_id.as_ref(),
+        &[stream_account.bump],
+    ];
+
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_context = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: stream_token_account.to_account_info(),
+            to: to_token_account.to_account_info(),
+            authority: stream_account.to_account_info(),
+            mint: token_mint.to_account_info(),
+        },
+        signer_seeds,
+    );
+
+    transfer_checked(cpi_context, amount, token_mint.decimals)
+}
@@ -1,7 +1,7 @@
 use anchor_lang::{prelude::*, system_program::{transfer, Transfer}};
 
 use crate::{
-    constants::VAULT_SIGNER_PREFIX, state::Vault, error::ErrorCode
+    state::{Vault, VaultSubAccount}, error::ErrorCode
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -16,16 +16,20 @@ pub struct WithdrawSol<'info> {
 
     #[account(
         mut,
-        constraint = vault.owner == withdrawer.key() @ErrorCode::VaultNotOwned
+        constraint = vault.owner == withdrawer.key() @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution
     )]
     pub vault: Box<Account<'info, Vault>>,
 
-    #[account(mut, 
-        seeds = [
-            VAULT_SIGNER_PREFIX.as_ref(),
-            vault.key().as_ref()
-        ],
-        bump = vault.signer_bump
+    /// Targets the sub-account's signer instead of the main vault signer when provided.
+    #[account(has_one = vault)]
+    pub sub_account: Option<Box<Account<'info, VaultSubAccount>>>,
+
+    #[account(
+        mut,
+        address = vault
+            .signer_seeds(vault.key(), sub_account.as_deref().map(|sub_account| &**sub_account))?
+            .address @ErrorCode::InvalidVaultSigner,
     )]
     /// CHECK: vault_signer is a PDA program signer. Data is never read or written to
     pub vault_signer: UncheckedAccount<'info>,
@@ -47,11 +51,11 @@ pub fn withdraw_sol_handler(ctx: Context<WithdrawSol>, params: WithdrawSolParams
         ErrorCode::NotEnoughLamports
     );
 
-    let seeds = &[
-        VAULT_SIGNER_PREFIX.as_ref(),
-        vault_key.as_ref(),
-        &[vault_account.signer_bump],
-    ];
+    let vault_signer_seeds = vault_account.signer_seeds(
+        vault_key,
+        ctx.accounts.sub_account.as_deref().map(|sub_account| &**sub_account),
+    )?;
+    let seeds = vault_signer_seeds.seeds();
     
     let signer_seeds = &[&seeds[..]];
 
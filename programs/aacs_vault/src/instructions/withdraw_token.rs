@@ -7,10 +7,9 @@ use anchor_spl::{
 };
 
 use crate::{
-    constants::VAULT_SIGNER_PREFIX, 
     error::ErrorCode, 
     events::TokenWithdrawn, 
-    state::Vault
+    state::{Vault, VaultSubAccount}
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -25,16 +24,19 @@ pub struct WithdrawToken<'info> {
     pub withdrawer: Signer<'info>,
 
      #[account(
-        constraint = &vault.owner == withdrawer.key @ErrorCode::VaultNotOwned 
+        constraint = &vault.owner == withdrawer.key @ErrorCode::VaultNotOwned,
+        constraint = !vault.is_executing @ErrorCode::ReentrantExecution 
     )]
     pub vault: Box<Account<'info, Vault>>,
 
-    #[account( 
-        seeds = [
-            VAULT_SIGNER_PREFIX.as_ref(),
-            vault.key().as_ref()
-        ],
-        bump = vault.signer_bump,
+    /// Targets the sub-account's signer instead of the main vault signer when provided.
+    #[account(has_one = vault)]
+    pub sub_account: Option<Box<Account<'info, VaultSubAccount>>>,
+
+    #[account(
+        address = vault
+            .signer_seeds(vault.key(), sub_account.as_deref().map(|sub_account| &**sub_account))?
+            .address @ErrorCode::InvalidVaultSigner,
     )]
     /// CHECK: this is signer of vault, holds SOL for that
     pub vault_signer: UncheckedAccount<'info>,
@@ -76,11 +78,11 @@ pub fn withdraw_token_handler(ctx: Context<WithdrawToken>, params: WithdrawToken
 
     let vault_key = vault_account.key();
     
-    let seeds = &[
-        VAULT_SIGNER_PREFIX.as_ref(),
-        vault_key.as_ref(),
-        &[vault_account.signer_bump],
-    ];
+    let vault_signer_seeds = vault_account.signer_seeds(
+        vault_key,
+        ctx.accounts.sub_account.as_deref().map(|sub_account| &**sub_account),
+    )?;
+    let seeds = vault_signer_seeds.seeds();
     
     let signer_seeds = &[&seeds[..]];
 
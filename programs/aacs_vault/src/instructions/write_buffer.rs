@@ -0,0 +1,42 @@
+use std::mem::size_of;
+
+use anchor_lang::prelude::*;
+
+use crate::{error::ErrorCode, state::ActionDataBuffer};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct WriteBufferParams {
+    pub offset: u32,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Accounts)]
+pub struct WriteBuffer<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(mut, has_one = authority)]
+    pub buffer: AccountLoader<'info, ActionDataBuffer>,
+}
+
+pub fn write_buffer_handler(ctx: Context<WriteBuffer>, params: WriteBufferParams) -> Result<()> {
+    let offset = params.offset as usize;
+
+    {
+        let buffer = ctx.accounts.buffer.load()?;
+
+        require!(!buffer.is_attached(), ErrorCode::ActionDataBufferFrozen);
+        require_gte!(
+            buffer.data_len as usize,
+            offset + params.bytes.len(),
+            ErrorCode::ActionDataBufferOverflow
+        );
+    }
+
+    let buffer_info = ctx.accounts.buffer.to_account_info();
+    let mut data = buffer_info.try_borrow_mut_data()?;
+
+    let start = 8 + size_of::<ActionDataBuffer>() + offset;
+    data[start..start + params.bytes.len()].copy_from_slice(&params.bytes);
+
+    Ok(())
+}
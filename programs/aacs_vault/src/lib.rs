@@ -1,8 +1,11 @@
 pub mod constants;
 pub mod error;
 pub mod events;
+pub mod execution;
 pub mod instructions;
+pub mod message;
 pub mod state;
+pub mod utils;
 
 use anchor_lang::prelude::*;
 
@@ -20,6 +23,13 @@ pub mod aacs_vault {
         create_vault_handler(ctx, params)
     }
 
+    pub fn open_sub_account(
+        ctx: Context<OpenSubAccount>,
+        params: OpenSubAccountParams,
+    ) -> Result<()> {
+        open_sub_account_handler(ctx, params)
+    }
+
     pub fn deposit_sol(ctx: Context<DepositSol>, params: DepositSolParams) -> Result<()> {
         deposit_sol_handler(ctx, params)
     }
@@ -43,18 +53,142 @@ pub mod aacs_vault {
         create_proposal_handler(ctx, params)
     }
 
+    pub fn create_proposal_from_message(
+        ctx: Context<CreateProposalFromMessage>,
+        params: CreateProposalFromMessageParams,
+    ) -> Result<()> {
+        create_proposal_from_message_handler(ctx, params)
+    }
+
+    pub fn create_committed_proposal(
+        ctx: Context<CreateCommittedProposal>,
+        params: CreateCommittedProposalParams,
+    ) -> Result<()> {
+        create_committed_proposal_handler(ctx, params)
+    }
+
+    pub fn create_template(
+        ctx: Context<CreateTemplate>,
+        params: CreateTemplateParams,
+    ) -> Result<()> {
+        create_template_handler(ctx, params)
+    }
+
+    pub fn instantiate_template(
+        ctx: Context<InstantiateTemplate>,
+        params: InstantiateTemplateParams,
+    ) -> Result<()> {
+        instantiate_template_handler(ctx, params)
+    }
+
+    pub fn close_template(ctx: Context<CloseTemplate>) -> Result<()> {
+        close_template_handler(ctx)
+    }
+
     pub fn append_actions(ctx: Context<AppendActions>, params: AppendActionsParams) -> Result<()> {
         append_actions_handler(ctx, params)
     }
 
+    pub fn remove_actions(ctx: Context<RemoveActions>) -> Result<()> {
+        remove_actions_handler(ctx)
+    }
+
+    pub fn create_buffer(ctx: Context<CreateBuffer>, params: CreateBufferParams) -> Result<()> {
+        create_buffer_handler(ctx, params)
+    }
+
+    pub fn write_buffer(ctx: Context<WriteBuffer>, params: WriteBufferParams) -> Result<()> {
+        write_buffer_handler(ctx, params)
+    }
+
+    pub fn attach_buffer(ctx: Context<AttachBuffer>, params: AttachBufferParams) -> Result<()> {
+        attach_buffer_handler(ctx, params)
+    }
+
+    pub fn close_buffer(ctx: Context<CloseBuffer>) -> Result<()> {
+        close_buffer_handler(ctx)
+    }
+
+    pub fn set_proposal_guards(
+        ctx: Context<SetProposalGuards>,
+        params: SetProposalGuardsParams,
+    ) -> Result<()> {
+        set_proposal_guards_handler(ctx, params)
+    }
+
+    pub fn set_proposal_dependencies(
+        ctx: Context<SetProposalDependencies>,
+        params: SetProposalDependenciesParams,
+    ) -> Result<()> {
+        set_proposal_dependencies_handler(ctx, params)
+    }
+
+    pub fn set_proposal_outflow_cap(
+        ctx: Context<SetProposalOutflowCap>,
+        params: SetOutflowCapParams,
+    ) -> Result<()> {
+        set_proposal_outflow_cap_handler(ctx, params)
+    }
+
     pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
         execute_proposal_handler(ctx)
     }
 
+    pub fn execute_proposals<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteProposals<'info>>,
+        params: ExecuteProposalsParams,
+    ) -> Result<()> {
+        execute_proposals_handler(ctx, params)
+    }
+
+    pub fn execute_committed_proposal(
+        ctx: Context<ExecuteProposal>,
+        params: ExecuteCommittedProposalParams,
+    ) -> Result<()> {
+        execute_committed_proposal_handler(ctx, params)
+    }
+
     pub fn execute_proposal_direct(
         ctx: Context<ExecuteProposalDirect>,
         params: ExecuteProposalDirectParams,
     ) -> Result<()> {
         execute_proposal_direct_handler(ctx, params)
     }
+
+    pub fn disable_direct_execution(ctx: Context<DisableDirectExecution>) -> Result<()> {
+        disable_direct_execution_handler(ctx)
+    }
+
+    pub fn set_program_allowlist(
+        ctx: Context<SetProgramAllowlist>,
+        params: SetProgramAllowlistParams,
+    ) -> Result<()> {
+        set_program_allowlist_handler(ctx, params)
+    }
+
+    pub fn set_proposal_limits(
+        ctx: Context<SetProposalLimits>,
+        params: SetProposalLimitsParams,
+    ) -> Result<()> {
+        set_proposal_limits_handler(ctx, params)
+    }
+
+    pub fn set_vault_outflow_cap(
+        ctx: Context<SetVaultOutflowCap>,
+        params: SetOutflowCapParams,
+    ) -> Result<()> {
+        set_vault_outflow_cap_handler(ctx, params)
+    }
+
+    pub fn create_stream(ctx: Context<CreateStream>, params: CreateStreamParams) -> Result<()> {
+        create_stream_handler(ctx, params)
+    }
+
+    pub fn withdraw_from_stream(ctx: Context<WithdrawFromStream>) -> Result<()> {
+        withdraw_from_stream_handler(ctx)
+    }
+
+    pub fn cancel_stream(ctx: Context<CancelStream>) -> Result<()> {
+        cancel_stream_handler(ctx)
+    }
 }
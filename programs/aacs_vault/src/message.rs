@@ -0,0 +1,260 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{ADDRESS_LOOKUP_TABLE_PROGRAM_ID, LOOKUP_TABLE_META_SIZE},
+    error::ErrorCode,
+    state::{AccountSpec, Action, ExecutionPolicy},
+};
+
+/// Bit set on the first byte of a versioned message, whose low bits hold the version.
+const MESSAGE_VERSION_PREFIX: u8 = 0x80;
+
+/// A legacy or v0 transaction message, borrowed from its wire encoding.
+pub struct TransactionMessage<'a> {
+    pub num_required_signatures: u8,
+    pub num_readonly_signed_accounts: u8,
+    pub num_readonly_unsigned_accounts: u8,
+    pub account_keys: &'a [Pubkey],
+    pub instructions: Vec<CompiledInstruction<'a>>,
+    /// Always empty for legacy messages.
+    pub address_table_lookups: Vec<AddressTableLookup<'a>>,
+}
+
+pub struct CompiledInstruction<'a> {
+    pub program_id_index: u8,
+    pub accounts: &'a [u8],
+    pub data: &'a [u8],
+}
+
+pub struct AddressTableLookup<'a> {
+    pub account_key: &'a Pubkey,
+    pub writable_indexes: &'a [u8],
+    pub readonly_indexes: &'a [u8],
+}
+
+impl<'a> TransactionMessage<'a> {
+    pub fn parse(mut bytes: &'a [u8]) -> Result<Self> {
+        let bytes = &mut bytes;
+
+        let is_versioned = bytes
+            .first()
+            .is_some_and(|prefix| prefix & MESSAGE_VERSION_PREFIX != 0);
+
+        if is_versioned {
+            let version = take(bytes, 1)?[0] & !MESSAGE_VERSION_PREFIX;
+            require_eq!(version, 0, ErrorCode::UnsupportedMessageVersion);
+        }
+
+        let header = take(bytes, 3)?;
+
+        let account_key_count = read_compact_u16(bytes)?;
+        let account_keys = bytemuck::cast_slice(take(bytes, 32 * account_key_count)?);
+
+        // Recent blockhash, meaningless once the message runs inside a proposal.
+        take(bytes, 32)?;
+
+        let instruction_count = read_compact_u16(bytes)?;
+        let mut instructions = Vec::with_capacity(instruction_count);
+        for _ in 0..instruction_count {
+            let program_id_index = take(bytes, 1)?[0];
+            let account_count = read_compact_u16(bytes)?;
+            let accounts = take(bytes, account_count)?;
+            let data_len = read_compact_u16(bytes)?;
+            let data = take(bytes, data_len)?;
+
+            instructions.push(CompiledInstruction {
+                program_id_index,
+                accounts,
+                data,
+            });
+        }
+
+        let mut address_table_lookups = vec![];
+        if is_versioned {
+            let lookup_count = read_compact_u16(bytes)?;
+            for _ in 0..lookup_count {
+                let account_key = bytemuck::from_bytes(take(bytes, 32)?);
+                let writable_count = read_compact_u16(bytes)?;
+                let writable_indexes = take(bytes, writable_count)?;
+                let readonly_count = read_compact_u16(bytes)?;
+                let readonly_indexes = take(bytes, readonly_count)?;
+
+                address_table_lookups.push(AddressTableLookup {
+                    account_key,
+                    writable_indexes,
+                    readonly_indexes,
+                });
+            }
+        }
+
+        require!(bytes.is_empty(), ErrorCode::InvalidTransactionMessage);
+
+        Ok(TransactionMessage {
+            num_required_signatures: header[0],
+            num_readonly_signed_accounts: header[1],
+            num_readonly_unsigned_accounts: header[2],
+            account_keys,
+            instructions,
+            address_table_lookups,
+        })
+    }
+
+    /// Resolves the full account key list: the static keys, then the writable and then the
+    /// readonly keys loaded from each lookup table, found among `lookup_table_accounts`.
+    pub fn resolve_account_keys(
+        &self,
+        lookup_table_accounts: &[AccountInfo],
+    ) -> Result<Vec<Pubkey>> {
+        let mut account_keys = self.account_keys.to_vec();
+        let mut readonly_keys = vec![];
+
+        for lookup in self.address_table_lookups.iter() {
+            let lookup_table = lookup_table_accounts
+                .iter()
+                .find(|account| account.key == lookup.account_key)
+                .ok_or(ErrorCode::InvalidAddressLookupTable)?;
+
+            require_keys_eq!(
+                *lookup_table.owner,
+                ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+                ErrorCode::InvalidAddressLookupTable
+            );
+
+            let data = lookup_table.try_borrow_data()?;
+            let addresses: &[Pubkey] = data
+                .get(LOOKUP_TABLE_META_SIZE..)
+                .filter(|addresses| addresses.len() % 32 == 0)
+                .map(bytemuck::cast_slice)
+                .ok_or(ErrorCode::InvalidAddressLookupTable)?;
+
+            let lookup_address = |index: &u8| {
+                addresses
+                    .get(*index as usize)
+                    .copied()
+                    .ok_or(error!(ErrorCode::InvalidAddressLookupTable))
+            };
+
+            for index in lookup.writable_indexes {
+                account_keys.push(lookup_address(index)?);
+            }
+            for index in lookup.readonly_indexes {
+                readonly_keys.push(lookup_address(index)?);
+            }
+        }
+
+        account_keys.extend(readonly_keys);
+
+        Ok(account_keys)
+    }
+
+    pub fn is_signer(&self, index: usize) -> bool {
+        index < self.num_required_signatures as usize
+    }
+
+    pub fn is_writable(&self, index: usize) -> bool {
+        let static_count = self.account_keys.len();
+        let required_signatures = self.num_required_signatures as usize;
+
+        if index >= static_count {
+            let writable_loaded: usize = self
+                .address_table_lookups
+                .iter()
+                .map(|lookup| lookup.writable_indexes.len())
+                .sum();
+
+            return index < static_count + writable_loaded;
+        }
+
+        if index < required_signatures {
+            index < required_signatures.saturating_sub(self.num_readonly_signed_accounts as usize)
+        } else {
+            index < static_count.saturating_sub(self.num_readonly_unsigned_accounts as usize)
+        }
+    }
+
+    /// Decompiles the instructions into atomic actions against a key table holding only
+    /// the keys they use. Signers found in `sub_account_signers`, pairs of a sub-account
+    /// index and its signer address, are marked to be signed for by that sub-account.
+    pub fn decompile(
+        &self,
+        resolved_account_keys: &[Pubkey],
+        sub_account_signers: &[(u8, Pubkey)],
+    ) -> Result<(Vec<Pubkey>, Vec<Action>)> {
+        let mut account_keys: Vec<Pubkey> = vec![];
+        let mut index_of = |message_index: u8| -> Result<u8> {
+            let pubkey = resolved_account_keys
+                .get(message_index as usize)
+                .ok_or(ErrorCode::InvalidTransactionMessage)?;
+
+            let index = match account_keys.iter().position(|key| key == pubkey) {
+                Some(index) => index,
+                None => {
+                    account_keys.push(*pubkey);
+                    account_keys.len() - 1
+                }
+            };
+
+            u8::try_from(index).map_err(|_| error!(ErrorCode::TooManyAccountKeys))
+        };
+
+        let mut actions = Vec::with_capacity(self.instructions.len());
+        for instruction in self.instructions.iter() {
+            let program_id_index = index_of(instruction.program_id_index)?;
+
+            let mut account_specs = Vec::with_capacity(instruction.accounts.len());
+            for message_index in instruction.accounts.iter().copied() {
+                let is_signer = self.is_signer(message_index as usize);
+                let account_index = index_of(message_index)?;
+
+                let sub_account_index = sub_account_signers
+                    .iter()
+                    .filter(|_| is_signer)
+                    .find(|(_, address)| address == &resolved_account_keys[message_index as usize])
+                    .map(|(index, _)| *index);
+
+                account_specs.push(AccountSpec {
+                    account_index,
+                    is_signer,
+                    is_writable: self.is_writable(message_index as usize),
+                    sub_account_index,
+                });
+            }
+
+            actions.push(Action::new(
+                program_id_index,
+                account_specs,
+                instruction.data.to_vec(),
+                None,
+                vec![],
+                ExecutionPolicy::Atomic,
+            ));
+        }
+
+        Ok((account_keys, actions))
+    }
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    require_gte!(bytes.len(), len, ErrorCode::InvalidTransactionMessage);
+
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+
+    Ok(taken)
+}
+
+/// Reads the "compact-u16" length prefix of the message wire format.
+fn read_compact_u16(bytes: &mut &[u8]) -> Result<usize> {
+    let mut value = 0usize;
+
+    for position in 0..3 {
+        let byte = take(bytes, 1)?[0];
+        value |= ((byte & 0x7f) as usize) << (7 * position);
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    err!(ErrorCode::InvalidTransactionMessage)
+}
@@ -1,18 +1,33 @@
 use anchor_lang::prelude::*;
 
-#[derive(Clone, InitSpace, AnchorSerialize, AnchorDeserialize)]
+use crate::error::ErrorCode;
+
+#[derive(Clone, Copy, InitSpace, AnchorSerialize, AnchorDeserialize)]
 pub struct AccountSpec {
-    pub pubkey: Pubkey,
+    /// Index into the account key table the action is executed against.
+    pub account_index: u8,
     pub is_signer: bool,
     pub is_writable: bool,
+    /// Index of the vault sub-account whose signer PDA signs for this account.
+    pub sub_account_index: Option<u8>,
 }
 
-impl From<&AccountSpec> for AccountMeta {
-    fn from(value: &AccountSpec) -> Self {
-        AccountMeta {
-            pubkey: value.pubkey,
-            is_signer: value.is_signer,
-            is_writable: value.is_writable,
-        }
+impl AccountSpec {
+    pub fn get_data_size(&self) -> usize {
+        1 + 1 + 1 + 1 + self.sub_account_index.map_or(0, |_| 1)
+    }
+
+    pub fn pubkey<'a>(&self, account_keys: &'a [Pubkey]) -> Result<&'a Pubkey> {
+        account_keys
+            .get(self.account_index as usize)
+            .ok_or(error!(ErrorCode::InvalidAccountIndex))
+    }
+
+    pub fn to_account_meta(&self, account_keys: &[Pubkey]) -> Result<AccountMeta> {
+        Ok(AccountMeta {
+            pubkey: *self.pubkey(account_keys)?,
+            is_signer: self.is_signer,
+            is_writable: self.is_writable,
+        })
     }
 }
@@ -1,24 +1,287 @@
 use anchor_lang::prelude::*;
 
-use super::AccountSpec;
+use crate::error::ErrorCode;
+
+use super::{AccountSpec, ExecutionPolicy, Placeholder};
 
 #[derive(Clone, AnchorSerialize, AnchorDeserialize)]
 pub struct Action {
-    pub program_id: Pubkey,
+    /// Index of the target program in the account key table.
+    pub program_id_index: u8,
     pub account_specs: Vec<AccountSpec>,
     pub data: Vec<u8>,
+    /// `ActionDataBuffer` holding the instruction data, in which case `data` is empty.
+    pub data_buffer: Option<DataBufferRef>,
+    /// Amounts in `data` filled in when the action is invoked.
+    pub placeholders: Vec<Placeholder>,
+    pub policy: ExecutionPolicy,
 }
 
 impl Action {
-    pub fn new(program_id: Pubkey, account_specs: Vec<AccountSpec>, data: Vec<u8>) -> Self {
+    pub fn new(
+        program_id_index: u8,
+        account_specs: Vec<AccountSpec>,
+        data: Vec<u8>,
+        data_buffer: Option<DataBufferRef>,
+        placeholders: Vec<Placeholder>,
+        policy: ExecutionPolicy,
+    ) -> Self {
         Action {
-            program_id,
+            program_id_index,
             account_specs,
             data,
+            data_buffer,
+            placeholders,
+            policy,
         }
     }
 
     pub fn get_data_size(&self) -> usize {
-        32 + 4 + (34 * self.account_specs.len()) + 4 + self.data.len()
+        let account_specs_size: usize = self
+            .account_specs
+            .iter()
+            .map(|account_spec| account_spec.get_data_size())
+            .sum();
+        let placeholders_size: usize = self
+            .placeholders
+            .iter()
+            .map(|placeholder| placeholder.get_data_size())
+            .sum();
+
+        1 + 4
+            + account_specs_size
+            + 4
+            + self.data.len()
+            + 1
+            + self.data_buffer.map_or(0, |_| DataBufferRef::INIT_SPACE)
+            + 4
+            + placeholders_size
+            + self.policy.get_data_size()
+    }
+
+    pub fn view(&self) -> ActionView<'_> {
+        ActionView {
+            program_id_index: self.program_id_index,
+            account_specs: BorshSlice::Decoded(&self.account_specs),
+            data: &self.data,
+            data_buffer: self.data_buffer,
+            placeholders: BorshSlice::Decoded(&self.placeholders),
+            policy: self.policy,
+        }
+    }
+}
+
+/// Reference from an action to the `ActionDataBuffer` holding its instruction data. The
+/// length and hash are part of the action, so the proposal commits to the data itself and
+/// not only to whichever buffer is found at the index.
+#[derive(Clone, Copy, InitSpace, AnchorSerialize, AnchorDeserialize)]
+pub struct DataBufferRef {
+    /// Index of the buffer in the account key table.
+    pub account_index: u8,
+    pub data_len: u32,
+    pub data_hash: [u8; 32],
+}
+
+enum BorshSlice<'a, T> {
+    Decoded(&'a [T]),
+    /// Borsh encoded items, checked when the view was parsed.
+    Encoded {
+        bytes: &'a [u8],
+        len: usize,
+    },
+}
+
+impl<T> Clone for BorshSlice<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for BorshSlice<'_, T> {}
+
+impl<'a, T: Copy + AnchorDeserialize + 'a> BorshSlice<'a, T> {
+    /// Parses a Borsh encoded `Vec<T>` off the front of `bytes`.
+    fn parse(bytes: &mut &'a [u8]) -> Result<Self> {
+        let len = u32::deserialize(bytes)? as usize;
+        let start = *bytes;
+        for _ in 0..len {
+            T::deserialize(bytes)?;
+        }
+
+        Ok(BorshSlice::Encoded {
+            bytes: &start[..start.len() - bytes.len()],
+            len,
+        })
     }
+
+    fn iter(self) -> impl Iterator<Item = Result<T>> + 'a {
+        let (decoded, mut encoded, len) = match self {
+            BorshSlice::Decoded(items) => (items, &[][..], 0),
+            BorshSlice::Encoded { bytes, len } => (&[][..], bytes, len),
+        };
+
+        decoded
+            .iter()
+            .map(|item| Ok(*item))
+            .chain((0..len).map(move |_| T::deserialize(&mut encoded).map_err(Into::into)))
+    }
+}
+
+/// An action borrowed either from an `Action` or from the Borsh encoding of one, so
+/// actions stored in account data can be validated and invoked without copying them.
+#[derive(Clone, Copy)]
+pub struct ActionView<'a> {
+    pub program_id_index: u8,
+    account_specs: BorshSlice<'a, AccountSpec>,
+    pub data: &'a [u8],
+    pub data_buffer: Option<DataBufferRef>,
+    placeholders: BorshSlice<'a, Placeholder>,
+    pub policy: ExecutionPolicy,
+}
+
+impl<'a> ActionView<'a> {
+    /// Parses the Borsh encoding of an `Action` off the front of `bytes`.
+    pub fn parse(bytes: &mut &'a [u8]) -> Result<Self> {
+        let program_id_index = u8::deserialize(bytes)?;
+
+        let account_specs = BorshSlice::parse(bytes)?;
+
+        let data_len = u32::deserialize(bytes)? as usize;
+        require_gte!(bytes.len(), data_len, ErrorCode::InvalidProposalActions);
+        let (data, rest) = bytes.split_at(data_len);
+        *bytes = rest;
+
+        let data_buffer = Option::<DataBufferRef>::deserialize(bytes)?;
+        let placeholders = BorshSlice::parse(bytes)?;
+        let policy = ExecutionPolicy::deserialize(bytes)?;
+
+        Ok(ActionView {
+            program_id_index,
+            account_specs,
+            data,
+            data_buffer,
+            placeholders,
+            policy,
+        })
+    }
+
+    pub fn account_specs(&self) -> impl Iterator<Item = Result<AccountSpec>> + 'a {
+        self.account_specs.iter()
+    }
+
+    pub fn placeholders(&self) -> impl Iterator<Item = Result<Placeholder>> + 'a {
+        self.placeholders.iter()
+    }
+
+    /// Checks every placeholder against the instruction data `data` of the action, whose
+    /// first `pinned_len` bytes must not be written.
+    pub fn validate_placeholders(
+        &self,
+        account_keys: &[Pubkey],
+        data: &[u8],
+        pinned_len: usize,
+    ) -> Result<()> {
+        for placeholder in self.placeholders() {
+            placeholder?.validate(account_keys, data, pinned_len)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn program_id<'k>(&self, account_keys: &'k [Pubkey]) -> Result<&'k Pubkey> {
+        account_keys
+            .get(self.program_id_index as usize)
+            .ok_or(error!(ErrorCode::InvalidAccountIndex))
+    }
+
+    /// Rejects actions that refer outside the key table, call back into this program or
+    /// hand the vault, or the proposal being executed, to another program as writable.
+    /// Actions reading their data from a buffer must not carry inline data, the
+    /// placeholders of other actions must fit their data.
+    pub fn validate_targets(
+        &self,
+        account_keys: &[Pubkey],
+        vault: &Pubkey,
+        proposal: Option<&Pubkey>,
+    ) -> Result<()> {
+        require_keys_neq!(
+            *self.program_id(account_keys)?,
+            crate::ID,
+            ErrorCode::SelfReferentialAction
+        );
+
+        if let Some(data_buffer) = self.data_buffer {
+            require!(self.data.is_empty(), ErrorCode::InvalidActionDataBuffer);
+            require_gt!(
+                account_keys.len(),
+                data_buffer.account_index as usize,
+                ErrorCode::InvalidAccountIndex
+            );
+        } else {
+            self.validate_placeholders(account_keys, self.data, 0)?;
+        }
+
+        for account_spec in self.account_specs() {
+            let account_spec = account_spec?;
+            let pubkey = account_spec.pubkey(account_keys)?;
+
+            if !account_spec.is_writable {
+                continue;
+            }
+
+            require_keys_neq!(*pubkey, *vault, ErrorCode::WritableVaultInAction);
+
+            if let Some(proposal) = proposal {
+                require_keys_neq!(*pubkey, *proposal, ErrorCode::WritableProposalInAction);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Iterates the actions encoded back to back in a `ProposalActions` action region.
+pub struct ActionViews<'a> {
+    bytes: &'a [u8],
+    remaining: u16,
+}
+
+impl<'a> ActionViews<'a> {
+    pub fn new(bytes: &'a [u8], count: u16) -> Self {
+        ActionViews {
+            bytes,
+            remaining: count,
+        }
+    }
+}
+
+impl<'a> Iterator for ActionViews<'a> {
+    type Item = Result<ActionView<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        self.remaining -= 1;
+        Some(ActionView::parse(&mut self.bytes))
+    }
+}
+
+/// Checks that an account key table has no repeated keys and fits `u8` indices.
+pub fn validate_account_keys(account_keys: &[Pubkey]) -> Result<()> {
+    require_gte!(
+        u8::MAX as usize + 1,
+        account_keys.len(),
+        ErrorCode::TooManyAccountKeys
+    );
+
+    for (index, pubkey) in account_keys.iter().enumerate() {
+        require!(
+            !account_keys[..index].contains(pubkey),
+            ErrorCode::DuplicateAccountKey
+        );
+    }
+
+    Ok(())
 }
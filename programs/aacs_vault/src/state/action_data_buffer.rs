@@ -0,0 +1,94 @@
+use std::mem::size_of;
+
+use anchor_lang::{prelude::*, solana_program::hash::hash, Discriminator};
+
+use crate::error::ErrorCode;
+
+use super::DataBufferRef;
+
+/// Header of a buffer holding the instruction data of one action, for data too large to
+/// submit with the action. The header is followed by `data_len` bytes of data, which the
+/// authority writes piecewise until the buffer is attached to a proposal and frozen.
+#[account(zero_copy)]
+pub struct ActionDataBuffer {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    /// Proposal whose actions may read the buffer, set once attached.
+    pub proposal: Pubkey,
+    /// Hash of the data, verified when the buffer is attached.
+    pub data_hash: [u8; 32],
+    pub data_len: u32,
+    pub is_attached: u8,
+    pub padding: [u8; 3],
+}
+
+impl ActionDataBuffer {
+    pub fn calculate_data_size(data_len: usize) -> usize {
+        size_of::<ActionDataBuffer>() + data_len
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.is_attached != 0
+    }
+
+    pub fn hash_data(data: &[u8]) -> [u8; 32] {
+        hash(data).to_bytes()
+    }
+
+    /// Splits the account data of a buffer into its header and data.
+    pub fn split(data: &[u8]) -> Result<(&ActionDataBuffer, &[u8])> {
+        require!(
+            data.len() >= 8 + size_of::<ActionDataBuffer>()
+                && data[..8] == *ActionDataBuffer::DISCRIMINATOR,
+            ErrorCode::InvalidActionDataBuffer
+        );
+
+        let (header, buffer_data) = data[8..].split_at(size_of::<ActionDataBuffer>());
+        let header: &ActionDataBuffer = bytemuck::from_bytes(header);
+
+        require_eq!(
+            buffer_data.len(),
+            header.data_len as usize,
+            ErrorCode::InvalidActionDataBuffer
+        );
+
+        Ok((header, buffer_data))
+    }
+
+    /// Returns the data of the buffer in `account`, which must be attached to `proposal`.
+    pub fn attached_data<'d>(
+        account: &AccountInfo,
+        data: &'d [u8],
+        proposal: Option<&Pubkey>,
+    ) -> Result<&'d [u8]> {
+        require_keys_eq!(*account.owner, crate::ID, ErrorCode::InvalidActionDataBuffer);
+
+        let (header, buffer_data) = ActionDataBuffer::split(data)?;
+
+        require!(
+            header.is_attached() && Some(&header.proposal) == proposal,
+            ErrorCode::InvalidActionDataBuffer
+        );
+
+        Ok(buffer_data)
+    }
+
+    /// Returns the data of the buffer in `account`, which must be attached to `proposal`
+    /// and hold the data `data_buffer` committed to. The hash was verified on attaching.
+    pub fn referenced_data<'d>(
+        account: &AccountInfo,
+        data: &'d [u8],
+        proposal: Option<&Pubkey>,
+        data_buffer: &DataBufferRef,
+    ) -> Result<&'d [u8]> {
+        let buffer_data = ActionDataBuffer::attached_data(account, data, proposal)?;
+        let (header, _) = ActionDataBuffer::split(data)?;
+
+        require!(
+            header.data_len == data_buffer.data_len && header.data_hash == data_buffer.data_hash,
+            ErrorCode::ActionDataBufferMismatch
+        );
+
+        Ok(buffer_data)
+    }
+}
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+/// Trace of a direct execution, one per vault and nonce.
+#[account]
+#[derive(InitSpace)]
+pub struct DirectExecutionRecord {
+    pub vault: Pubkey,
+    pub caller: Pubkey,
+    pub nonce: u64,
+    /// Hash of the account keys and actions, as `ProposalActions::chain_hash` computes it
+    /// for a single chunk.
+    pub actions_hash: [u8; 32],
+    pub executed_date: i64,
+    pub bump: u8,
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+use super::ActionView;
+
+/// How an action's failure affects the rest of the proposal. Failures inside an invoked
+/// program always abort the transaction, so only actions whose prechecks fail can be
+/// skipped: a token program action writes an account that is not a live account of that
+/// program, as with a closed token account.
+#[derive(Default, Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum ExecutionPolicy {
+    /// Always invoked; any failure aborts the proposal.
+    #[default]
+    Atomic,
+    /// Skipped when its prechecks fail. Only token program actions have prechecks, so
+    /// actions of other programs are always invoked and abort the proposal if they fail.
+    ContinueOnError,
+    /// Skipped, together with the rest of its group, when any member's prechecks fail.
+    /// Members of a group must be consecutive and, in a stored proposal, in the same chunk.
+    Group { id: u8 },
+}
+
+impl ExecutionPolicy {
+    pub fn get_data_size(&self) -> usize {
+        match self {
+            ExecutionPolicy::Group { .. } => 2,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum ActionOutcome {
+    Executed,
+    Skipped,
+}
+
+/// Group ids used by `actions`, one bit per id.
+pub fn action_group_ids<'a>(
+    actions: impl IntoIterator<Item = Result<ActionView<'a>>>,
+) -> Result<[u8; 32]> {
+    let mut ids = [0u8; 32];
+
+    for action in actions {
+        if let ExecutionPolicy::Group { id } = action?.policy {
+            ids[id as usize / 8] |= 1 << (id % 8);
+        }
+    }
+
+    Ok(ids)
+}
+
+/// Checks that the members of every action group are consecutive.
+pub fn validate_action_groups<'a>(
+    actions: impl IntoIterator<Item = Result<ActionView<'a>>>,
+) -> Result<()> {
+    let mut closed_groups: Vec<u8> = vec![];
+    let mut current_group = None;
+
+    for action in actions {
+        let group = match action?.policy {
+            ExecutionPolicy::Group { id } => Some(id),
+            _ => None,
+        };
+
+        if group != current_group {
+            if let Some(id) = current_group {
+                closed_groups.push(id);
+            }
+
+            if let Some(id) = group {
+                require!(
+                    !closed_groups.contains(&id),
+                    ErrorCode::NonContiguousActionGroup
+                );
+            }
+
+            current_group = group;
+        }
+    }
+
+    Ok(())
+}
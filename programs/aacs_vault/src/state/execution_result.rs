@@ -0,0 +1,52 @@
+use anchor_lang::{prelude::*, solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE};
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct ActionResult {
+    pub action_index: u16,
+    /// Program that set the return data, which may be a program invoked by the action target.
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+impl ActionResult {
+    pub fn get_data_size(&self) -> usize {
+        2 + 32 + 4 + self.data.len()
+    }
+}
+
+#[account]
+pub struct ProposalExecutionResult {
+    pub proposal: Pubkey,
+    pub vault: Pubkey,
+    pub executed_date: i64,
+    pub bump: u8,
+    /// Set when trailing results were left out to fit the account. The `ProposalExecuted`
+    /// event carries all of them.
+    pub truncated: bool,
+    pub results: Vec<ActionResult>,
+}
+
+impl ProposalExecutionResult {
+    pub fn calculate_data_size(results: &[ActionResult]) -> usize {
+        let results_size: usize = results.iter().map(|result| result.get_data_size()).sum();
+        32 + 32 + 8 + 1 + 1 + 4 + results_size
+    }
+
+    /// Leading `results` the account can hold, and whether any were left out. The account
+    /// is created by the executing instruction, which may grow an account by at most
+    /// `MAX_PERMITTED_DATA_INCREASE` bytes.
+    pub fn fit_results(results: &[ActionResult]) -> (Vec<ActionResult>, bool) {
+        let mut size = 8 + Self::calculate_data_size(&[]);
+        let stored = results
+            .iter()
+            .take_while(|result| {
+                size += result.get_data_size();
+                size <= MAX_PERMITTED_DATA_INCREASE
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let truncated = stored.len() < results.len();
+
+        (stored, truncated)
+    }
+}
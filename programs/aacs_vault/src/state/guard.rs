@@ -0,0 +1,102 @@
+use anchor_lang::{prelude::*, Owners};
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::error::ErrorCode;
+
+/// A condition on one account, checked before a proposal's actions run or after they all
+/// ran. The account is passed among the remaining accounts of the executing instruction.
+#[derive(Clone, Copy, InitSpace, AnchorSerialize, AnchorDeserialize)]
+pub struct Guard {
+    pub account: Pubkey,
+    pub condition: GuardCondition,
+}
+
+#[derive(Clone, Copy, InitSpace, AnchorSerialize, AnchorDeserialize)]
+pub enum GuardCondition {
+    MinLamports {
+        min_lamports: u64,
+    },
+    /// Amount held by a token account.
+    MinTokenAmount {
+        min_amount: u64,
+    },
+    OwnedBy {
+        owner: Pubkey,
+    },
+    /// Lamports lost over the execution, only valid after execution.
+    MaxLamportsDecrease {
+        max_decrease: u64,
+    },
+    /// Tokens lost over the execution, only valid after execution.
+    MaxTokenDecrease {
+        max_decrease: u64,
+    },
+}
+
+impl Guard {
+    /// Whether the condition compares against the state before execution.
+    pub fn is_relative(&self) -> bool {
+        matches!(
+            self.condition,
+            GuardCondition::MaxLamportsDecrease { .. } | GuardCondition::MaxTokenDecrease { .. }
+        )
+    }
+
+    fn find_account<'a, 'info>(
+        &self,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<&'a AccountInfo<'info>> {
+        accounts
+            .iter()
+            .find(|account| account.key == &self.account)
+            .ok_or_else(|| {
+                msg!("Guard account {} was not passed", self.account);
+                error!(ErrorCode::MissingGuardAccount)
+            })
+    }
+
+    /// The lamports or token amount the condition is measured on, zero for ownership.
+    pub fn measure(&self, accounts: &[AccountInfo]) -> Result<u64> {
+        let account = self.find_account(accounts)?;
+
+        match self.condition {
+            GuardCondition::MinLamports { .. } | GuardCondition::MaxLamportsDecrease { .. } => {
+                Ok(account.lamports())
+            }
+            GuardCondition::MinTokenAmount { .. } | GuardCondition::MaxTokenDecrease { .. } => {
+                require!(
+                    TokenAccount::owners().contains(account.owner),
+                    ErrorCode::InvalidGuard
+                );
+
+                let token_account =
+                    TokenAccount::try_deserialize(&mut &account.try_borrow_data()?[..])
+                        .map_err(|_| ErrorCode::InvalidGuard)?;
+                Ok(token_account.amount)
+            }
+            GuardCondition::OwnedBy { .. } => Ok(0),
+        }
+    }
+
+    /// Checks the condition, given what `measure` returned before execution.
+    pub fn check(&self, guard_index: usize, accounts: &[AccountInfo], baseline: u64) -> Result<()> {
+        let value = self.measure(accounts)?;
+
+        let holds = match self.condition {
+            GuardCondition::MinLamports { min_lamports: min }
+            | GuardCondition::MinTokenAmount { min_amount: min } => value >= min,
+            GuardCondition::OwnedBy { owner } => *self.find_account(accounts)?.owner == owner,
+            GuardCondition::MaxLamportsDecrease { max_decrease }
+            | GuardCondition::MaxTokenDecrease { max_decrease } => {
+                baseline.saturating_sub(value) <= max_decrease
+            }
+        };
+
+        if !holds {
+            msg!("Guard {} on {} was violated", guard_index, self.account);
+            return err!(ErrorCode::GuardViolated);
+        }
+
+        Ok(())
+    }
+}
@@ -1,9 +1,35 @@
 mod account_spec;
 mod action;
+mod action_data_buffer;
+mod direct_execution_record;
+mod execution_policy;
+mod execution_result;
+mod guard;
+mod outflow_cap;
+mod placeholder;
+mod program_allowlist;
 mod proposal;
+mod proposal_actions;
+mod proposal_limits;
+mod proposal_template;
+mod stream;
+mod sub_account;
 mod vault;
 
 pub use account_spec::*;
 pub use action::*;
+pub use action_data_buffer::*;
+pub use direct_execution_record::*;
+pub use execution_policy::*;
+pub use execution_result::*;
+pub use guard::*;
+pub use outflow_cap::*;
+pub use placeholder::*;
+pub use program_allowlist::*;
 pub use proposal::*;
+pub use proposal_actions::*;
+pub use proposal_limits::*;
+pub use proposal_template::*;
+pub use stream::*;
+pub use sub_account::*;
 pub use vault::*;
@@ -0,0 +1,256 @@
+use anchor_lang::{prelude::*, Owners};
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{constants::MAX_TOKEN_OUTFLOW_CAPS, error::ErrorCode};
+
+use super::VaultSubAccount;
+
+/// Ceiling on what one execution may move out of the vault signers, whatever its actions
+/// do. Tokens of mints without a cap are not limited.
+#[derive(Clone, Default, InitSpace, AnchorSerialize, AnchorDeserialize)]
+pub struct OutflowCap {
+    pub max_lamports: Option<u64>,
+    #[max_len(MAX_TOKEN_OUTFLOW_CAPS)]
+    pub token_caps: Vec<TokenOutflowCap>,
+}
+
+#[derive(Clone, Copy, InitSpace, AnchorSerialize, AnchorDeserialize)]
+pub struct TokenOutflowCap {
+    pub mint: Pubkey,
+    pub max_amount: u64,
+}
+
+impl OutflowCap {
+    pub fn get_data_size(&self) -> usize {
+        1 + self.max_lamports.map_or(0, |_| 8)
+            + 4
+            + TokenOutflowCap::INIT_SPACE * self.token_caps.len()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require_gte!(
+            MAX_TOKEN_OUTFLOW_CAPS,
+            self.token_caps.len(),
+            ErrorCode::TooManyTokenOutflowCaps
+        );
+
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max_lamports.is_none() && self.token_caps.is_empty()
+    }
+
+    pub fn check(&self, outflows: &Outflows) -> Result<()> {
+        if let Some(max_lamports) = self.max_lamports {
+            if outflows.lamports > max_lamports {
+                msg!(
+                    "Outflow of {} lamports exceeds the cap of {}",
+                    outflows.lamports,
+                    max_lamports
+                );
+                return err!(ErrorCode::OutflowCapExceeded);
+            }
+        }
+
+        for token_cap in self.token_caps.iter() {
+            let amount = outflows.token_amount(&token_cap.mint);
+
+            if amount > token_cap.max_amount {
+                msg!(
+                    "Outflow of {} tokens of mint {} exceeds the cap of {}",
+                    amount,
+                    token_cap.mint,
+                    token_cap.max_amount
+                );
+                return err!(ErrorCode::OutflowCapExceeded);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Amounts that left the vault signers and their token accounts during an execution.
+pub struct Outflows {
+    pub lamports: u64,
+    /// Total decrease of the vault signers' token accounts, per mint.
+    pub tokens: Vec<(Pubkey, u64)>,
+}
+
+impl Outflows {
+    pub fn token_amount(&self, mint: &Pubkey) -> u64 {
+        self.tokens
+            .iter()
+            .find(|(token_mint, _)| token_mint == mint)
+            .map_or(0, |(_, amount)| *amount)
+    }
+}
+
+/// A token account of a vault signer as it was before any action ran.
+struct TokenAccountSnapshot {
+    key: Pubkey,
+    owner: Pubkey,
+    mint: Pubkey,
+    amount: u64,
+    delegate: Option<Pubkey>,
+    delegated_amount: u64,
+    close_authority: Option<Pubkey>,
+}
+
+impl TokenAccountSnapshot {
+    fn new(key: Pubkey, token_account: &TokenAccount) -> Self {
+        TokenAccountSnapshot {
+            key,
+            owner: token_account.owner,
+            mint: token_account.mint,
+            amount: token_account.amount,
+            delegate: token_account.delegate.into(),
+            delegated_amount: token_account.delegated_amount,
+            close_authority: token_account.close_authority.into(),
+        }
+    }
+
+    /// How much of the snapshotted amount is gone. An account that was closed, handed to
+    /// another owner, or whose delegate or close authority was changed counts as emptied,
+    /// since whoever was given control can take everything later.
+    fn decrease(&self, accounts: &[AccountInfo]) -> u64 {
+        let token_account = accounts
+            .iter()
+            .find(|account| account.key == &self.key)
+            .and_then(|account| decode_token_account(account));
+
+        match token_account {
+            Some(token_account)
+                if token_account.owner == self.owner
+                    && Option::<Pubkey>::from(token_account.delegate) == self.delegate
+                    && token_account.delegated_amount <= self.delegated_amount
+                    && Option::<Pubkey>::from(token_account.close_authority)
+                        == self.close_authority =>
+            {
+                self.amount.saturating_sub(token_account.amount)
+            }
+            _ => self.amount,
+        }
+    }
+}
+
+/// A vault signer as it was before any action ran.
+struct SignerSnapshot {
+    key: Pubkey,
+    lamports: u64,
+    owner: Pubkey,
+    data_len: usize,
+}
+
+impl SignerSnapshot {
+    fn new(account: &AccountInfo) -> Self {
+        SignerSnapshot {
+            key: *account.key,
+            lamports: account.lamports(),
+            owner: *account.owner,
+            data_len: account.data_len(),
+        }
+    }
+
+    /// How many of the snapshotted lamports are gone. A signer that was assigned to another
+    /// program or allocated data counts as emptied, since its new owner controls them all.
+    fn decrease(&self, account: Option<&AccountInfo>) -> u64 {
+        match account {
+            Some(account)
+                if account.owner == &self.owner && account.data_len() == self.data_len =>
+            {
+                self.lamports.saturating_sub(account.lamports())
+            }
+            _ => self.lamports,
+        }
+    }
+}
+
+/// Balances of the vault signers, and of the token accounts they own among the passed
+/// accounts, before any action runs. Covers the main vault signer and the signer of every
+/// sub-account whose `VaultSubAccount` is passed, which are all the vault signers an
+/// execution signs for.
+pub struct OutflowSnapshot {
+    signers: Vec<SignerSnapshot>,
+    token_accounts: Vec<TokenAccountSnapshot>,
+}
+
+impl OutflowSnapshot {
+    pub fn take(vault: Pubkey, vault_signer: &AccountInfo, accounts: &[AccountInfo]) -> Result<Self> {
+        let mut signers = vec![SignerSnapshot::new(vault_signer)];
+
+        for signer in VaultSubAccount::opened_signers(accounts, vault)? {
+            let snapshot = match accounts.iter().find(|account| account.key == &signer) {
+                Some(account) => SignerSnapshot::new(account),
+                None => SignerSnapshot {
+                    key: signer,
+                    lamports: 0,
+                    owner: System::id(),
+                    data_len: 0,
+                },
+            };
+
+            signers.push(snapshot);
+        }
+
+        let mut token_accounts: Vec<TokenAccountSnapshot> = vec![];
+
+        for account in accounts {
+            if token_accounts.iter().any(|snapshot| &snapshot.key == account.key) {
+                continue;
+            }
+
+            if let Some(token_account) = decode_token_account(account) {
+                if signers.iter().any(|signer| signer.key == token_account.owner) {
+                    token_accounts.push(TokenAccountSnapshot::new(*account.key, &token_account));
+                }
+            }
+        }
+
+        Ok(OutflowSnapshot {
+            signers,
+            token_accounts,
+        })
+    }
+
+    /// Compares the balances in the same accounts against the snapshot. Each signer and
+    /// token account contributes its own decrease, so moving funds between vault signers
+    /// also counts against the cap.
+    pub fn outflows(&self, vault_signer: &AccountInfo, accounts: &[AccountInfo]) -> Outflows {
+        let lamports = self
+            .signers
+            .iter()
+            .map(|signer| {
+                if &signer.key == vault_signer.key {
+                    signer.decrease(Some(vault_signer))
+                } else {
+                    signer.decrease(accounts.iter().find(|account| account.key == &signer.key))
+                }
+            })
+            .fold(0u64, u64::saturating_add);
+
+        let mut tokens: Vec<(Pubkey, u64)> = vec![];
+
+        for snapshot in self.token_accounts.iter() {
+            let decrease = snapshot.decrease(accounts);
+
+            match tokens.iter_mut().find(|(mint, _)| mint == &snapshot.mint) {
+                Some((_, total)) => *total = total.saturating_add(decrease),
+                None => tokens.push((snapshot.mint, decrease)),
+            }
+        }
+
+        Outflows { lamports, tokens }
+    }
+}
+
+/// Decodes `account` if it is a token account.
+fn decode_token_account(account: &AccountInfo) -> Option<TokenAccount> {
+    if !TokenAccount::owners().contains(account.owner) {
+        return None;
+    }
+
+    let data = account.try_borrow_data().ok()?;
+    TokenAccount::try_deserialize(&mut &data[..]).ok()
+}
@@ -0,0 +1,114 @@
+use anchor_lang::{prelude::*, Owners};
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{constants::MAX_BASIS_POINTS, error::ErrorCode};
+
+/// A little-endian `u64` amount in the instruction data of an action, written when the
+/// action is invoked.
+#[derive(Clone, Copy, Debug, AnchorSerialize, AnchorDeserialize)]
+pub struct Placeholder {
+    /// Offset of the amount in the instruction data.
+    pub offset: u16,
+    pub source: AmountSource,
+    /// Share of the source amount written, `MAX_BASIS_POINTS` for all of it.
+    pub basis_points: u16,
+}
+
+#[derive(Clone, Copy, Debug, AnchorSerialize, AnchorDeserialize)]
+pub enum AmountSource {
+    /// Balance of the token account at `account_index` in the account key table.
+    TokenBalance { account_index: u8 },
+    /// Lamports the vault signer holds above `reserve`.
+    VaultLamports { reserve: u64 },
+}
+
+impl Placeholder {
+    pub fn get_data_size(&self) -> usize {
+        let source_size = match self.source {
+            AmountSource::TokenBalance { .. } => 1,
+            AmountSource::VaultLamports { .. } => 8,
+        };
+
+        2 + 1 + source_size + 2
+    }
+
+    /// Checks the amount fits in `data` after its first `pinned_len` bytes and the source
+    /// refers inside the key table.
+    pub fn validate(&self, account_keys: &[Pubkey], data: &[u8], pinned_len: usize) -> Result<()> {
+        require_gte!(
+            MAX_BASIS_POINTS,
+            self.basis_points,
+            ErrorCode::InvalidPlaceholder
+        );
+        require_gte!(
+            self.offset as usize,
+            pinned_len,
+            ErrorCode::InvalidPlaceholder
+        );
+        require_gte!(
+            data.len(),
+            self.offset as usize + 8,
+            ErrorCode::InvalidPlaceholder
+        );
+
+        if let AmountSource::TokenBalance { account_index } = self.source {
+            require_gt!(
+                account_keys.len(),
+                account_index as usize,
+                ErrorCode::InvalidPlaceholder
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Resolves the amount against the key table accounts, in table order.
+    pub fn resolve(&self, accounts: &[AccountInfo], vault_signer: &Pubkey) -> Result<u64> {
+        let source_amount = match self.source {
+            AmountSource::TokenBalance { account_index } => {
+                let account = accounts
+                    .get(account_index as usize)
+                    .ok_or(ErrorCode::InvalidPlaceholder)?;
+
+                require!(
+                    TokenAccount::owners().contains(account.owner),
+                    ErrorCode::InvalidPlaceholder
+                );
+
+                TokenAccount::try_deserialize(&mut &account.try_borrow_data()?[..])
+                    .map_err(|_| ErrorCode::InvalidPlaceholder)?
+                    .amount
+            }
+            AmountSource::VaultLamports { reserve } => {
+                let account = accounts
+                    .iter()
+                    .find(|account| account.key == vault_signer)
+                    .ok_or(ErrorCode::InvalidPlaceholder)?;
+
+                account.lamports().saturating_sub(reserve)
+            }
+        };
+
+        // The product of a u64 and basis points fits a u128 and the share fits a u64.
+        Ok((source_amount as u128 * self.basis_points as u128 / MAX_BASIS_POINTS as u128) as u64)
+    }
+
+    /// Writes `amount` over the placeholder bytes of `data`.
+    pub fn write(&self, data: &mut [u8], amount: u64) -> Result<()> {
+        let offset = self.offset as usize;
+
+        data.get_mut(offset..offset + 8)
+            .ok_or(ErrorCode::InvalidPlaceholder)?
+            .copy_from_slice(&amount.to_le_bytes());
+
+        Ok(())
+    }
+}
+
+/// An amount written into an action's instruction data during execution.
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct PlaceholderSubstitution {
+    pub action_index: u16,
+    pub offset: u16,
+    pub amount: u64,
+}
@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+use super::Vault;
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize)]
+pub struct AllowedProgram {
+    pub program_id: Pubkey,
+    /// Instruction data prefixes the program may be called with. Empty allows any instruction.
+    pub discriminators: Vec<Vec<u8>>,
+}
+
+impl AllowedProgram {
+    pub fn get_data_size(&self) -> usize {
+        let discriminators_size: usize = self
+            .discriminators
+            .iter()
+            .map(|discriminator| 4 + discriminator.len())
+            .sum();
+
+        32 + 4 + discriminators_size
+    }
+
+    pub fn allows(&self, data: &[u8]) -> bool {
+        self.discriminators.is_empty()
+            || self
+                .discriminators
+                .iter()
+                .any(|discriminator| data.starts_with(discriminator))
+    }
+
+    /// Length of the longest discriminator. Instruction data up to it must stay as proposed.
+    pub fn pinned_len(&self) -> usize {
+        self.discriminators
+            .iter()
+            .map(|discriminator| discriminator.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[account]
+pub struct ProgramAllowlist {
+    pub vault: Pubkey,
+    pub bump: u8,
+    pub programs: Vec<AllowedProgram>,
+}
+
+impl ProgramAllowlist {
+    pub fn calculate_data_size(programs: &[AllowedProgram]) -> usize {
+        let programs_size: usize = programs.iter().map(|program| program.get_data_size()).sum();
+        32 + 1 + 4 + programs_size
+    }
+
+    /// Checks an action target against the vault's allowlist. Vaults without an enabled
+    /// allowlist accept any target.
+    pub fn check_target(
+        vault: &Vault,
+        allowlist: Option<&ProgramAllowlist>,
+        program_id: &Pubkey,
+        data: &[u8],
+    ) -> Result<()> {
+        let Some(allowed_program) = Self::find_allowed(vault, allowlist, program_id)? else {
+            return Ok(());
+        };
+
+        if !allowed_program.allows(data) {
+            msg!("Instruction of program {} is not on the vault allowlist", program_id);
+            return err!(ErrorCode::InstructionNotAllowed);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the vault's allowlist accepts `program_id`, for actions whose
+    /// instruction data is not known yet.
+    pub fn check_program(
+        vault: &Vault,
+        allowlist: Option<&ProgramAllowlist>,
+        program_id: &Pubkey,
+    ) -> Result<()> {
+        Self::find_allowed(vault, allowlist, program_id).map(|_| ())
+    }
+
+    /// The allowlist entry of `program_id`, or `None` if the vault's allowlist is disabled.
+    fn find_allowed<'a>(
+        vault: &Vault,
+        allowlist: Option<&'a ProgramAllowlist>,
+        program_id: &Pubkey,
+    ) -> Result<Option<&'a AllowedProgram>> {
+        if !vault.allowlist_enabled {
+            return Ok(None);
+        }
+
+        let allowlist = allowlist.ok_or(ErrorCode::ProgramAllowlistRequired)?;
+
+        let Some(allowed_program) = allowlist
+            .programs
+            .iter()
+            .find(|allowed_program| &allowed_program.program_id == program_id)
+        else {
+            msg!("Program {} is not on the vault allowlist", program_id);
+            return err!(ErrorCode::ProgramNotAllowed);
+        };
+
+        Ok(Some(allowed_program))
+    }
+
+    /// Length of the instruction data prefix the vault's allowlist pins for `program_id`.
+    /// Placeholders must not write into it, or an allowed instruction could be turned
+    /// into another one when the action is invoked.
+    pub fn pinned_prefix_len(
+        vault: &Vault,
+        allowlist: Option<&ProgramAllowlist>,
+        program_id: &Pubkey,
+    ) -> usize {
+        if !vault.allowlist_enabled {
+            return 0;
+        }
+
+        allowlist
+            .and_then(|allowlist| {
+                allowlist
+                    .programs
+                    .iter()
+                    .find(|allowed_program| &allowed_program.program_id == program_id)
+            })
+            .map_or(0, AllowedProgram::pinned_len)
+    }
+}
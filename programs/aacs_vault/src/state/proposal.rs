@@ -1,6 +1,11 @@
 use anchor_lang::prelude::*;
 
-use super::Action;
+use crate::{constants::EPHEMERAL_SIGNER_PREFIX, error::ErrorCode};
+
+use super::{
+    action_group_ids, ActionOutcome, ActionView, Guard, OutflowCap, ProposalActions,
+    ProposalActionsData,
+};
 
 #[account]
 pub struct Proposal {
@@ -9,18 +14,190 @@ pub struct Proposal {
     pub created_date: i64,
     pub expiry_date: i64,
     pub is_executed: bool,
+    /// Bumps of the proposal's ephemeral signer PDAs, indexed by signer index.
+    pub ephemeral_signer_bumps: Vec<u8>,
+    /// Keys that must sign the executing transaction besides the vault owner.
+    pub co_signers: Vec<Pubkey>,
+    /// Proposals of the same vault that must be completed before this one executes.
+    pub dependencies: Vec<Pubkey>,
+    /// Conditions checked before the first action runs.
+    pub pre_guards: Vec<Guard>,
+    /// Conditions checked after every action ran.
+    pub post_guards: Vec<Guard>,
+    /// Applies to this proposal's execution on top of the vault's outflow cap.
+    pub outflow_cap: Option<OutflowCap>,
     pub name: String,
-    pub actions: Vec<Action>,
+    /// Whether the actions live in `ProposalActions` chunks or are only committed to by hash.
+    pub kind: ProposalKind,
+    /// Total number of actions across all `ProposalActions` chunks.
+    pub action_count: u16,
+    /// Number of `ProposalActions` chunks, which are indexed from zero.
+    pub chunk_count: u16,
+    /// Running hash over every chunk in order, see `ProposalActions::chain_hash`. A committed
+    /// proposal hashes its actions as a single chunk.
+    pub actions_hash: [u8; 32],
+    /// Size of a committed proposal's account key table and serialized actions, which the
+    /// actions supplied at execution must match. Unused by stored proposals.
+    pub actions_size: u32,
+    /// Group ids used by the chunks, one bit per id. Groups are prechecked and run within
+    /// a single chunk, so each id belongs to one chunk.
+    pub action_groups: [u8; 32],
+    /// Outcome of every action, indexed by action index, once the proposal is executed.
+    pub action_outcomes: Vec<ActionOutcome>,
 }
 
 impl Proposal {
-    pub fn calculate_data_size(name: &str, actions: &[Action]) -> usize {
-        let action_data_size: usize = actions.iter().map(|action| action.get_data_size()).sum();
-        32 + 1 + 8 + 8 + 1 + 4 + name.len() + 4 + action_data_size
+    pub fn calculate_data_size(
+        name: &str,
+        ephemeral_signer_count: usize,
+        co_signer_count: usize,
+        dependency_count: usize,
+        guard_count: usize,
+        outflow_cap: Option<&OutflowCap>,
+        action_outcome_count: usize,
+    ) -> usize {
+        32 + 1 + 8 + 8 + 1 + 4 + ephemeral_signer_count + 4 + 32 * co_signer_count
+            + 4 + 32 * dependency_count
+            + 4 + 4 + Guard::INIT_SPACE * guard_count
+            + 1 + outflow_cap.map_or(0, OutflowCap::get_data_size)
+            + 4 + name.len() + 1 + 2 + 2 + 32 + 4 + 32 + 4 + action_outcome_count
+    }
+
+    /// Checks that every co-signer signed the transaction passing `accounts`.
+    pub fn check_co_signers(&self, accounts: &[AccountInfo]) -> Result<()> {
+        for co_signer in self.co_signers.iter() {
+            if !accounts
+                .iter()
+                .any(|account| account.key == co_signer && account.is_signer)
+            {
+                msg!("Proposal co-signer {} did not sign", co_signer);
+                return err!(ErrorCode::MissingCoSigner);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every dependency, found among `accounts`, is a proposal of the same vault
+    /// that completed with all of its actions executed.
+    pub fn check_dependencies(&self, accounts: &[AccountInfo]) -> Result<()> {
+        for dependency in self.dependencies.iter() {
+            let dependency_account = Self::load_dependency(accounts, dependency, &self.vault)?;
+
+            if dependency_account.proposal_stage != ProposalStage::Completed {
+                msg!("Proposal dependency {} is not completed", dependency);
+                return err!(ErrorCode::DependencyNotCompleted);
+            }
+
+            // Skipped actions also complete a proposal, but leave its effects partial.
+            if dependency_account
+                .action_outcomes
+                .iter()
+                .any(|outcome| *outcome != ActionOutcome::Executed)
+            {
+                msg!("Proposal dependency {} skipped some of its actions", dependency);
+                return err!(ErrorCode::DependencyNotCompleted);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the proposal `dependency` of `vault` from `accounts`.
+    pub fn load_dependency(
+        accounts: &[AccountInfo],
+        dependency: &Pubkey,
+        vault: &Pubkey,
+    ) -> Result<Proposal> {
+        let account = accounts
+            .iter()
+            .find(|account| account.key == dependency)
+            .ok_or(ErrorCode::MissingDependencyAccount)?;
+
+        require_keys_eq!(*account.owner, crate::ID, ErrorCode::InvalidDependency);
+
+        let dependency_account = Proposal::try_deserialize(&mut &account.try_borrow_data()?[..])
+            .map_err(|_| ErrorCode::InvalidDependency)?;
+
+        require_keys_eq!(dependency_account.vault, *vault, ErrorCode::InvalidDependency);
+
+        Ok(dependency_account)
+    }
+
+    pub fn guard_count(&self) -> usize {
+        self.pre_guards.len() + self.post_guards.len()
+    }
+
+    /// Claims the group ids of the actions of a new chunk. An id already used by an earlier
+    /// chunk is rejected, as is a group continuing from the previous chunk.
+    pub fn claim_action_groups<'a>(
+        &mut self,
+        actions: impl IntoIterator<Item = Result<ActionView<'a>>>,
+    ) -> Result<()> {
+        let ids = action_group_ids(actions)?;
+
+        require!(
+            self.action_groups
+                .iter()
+                .zip(ids.iter())
+                .all(|(used, id)| used & id == 0),
+            ErrorCode::ActionGroupSpansChunks
+        );
+
+        for (used, id) in self.action_groups.iter_mut().zip(ids.iter()) {
+            *used |= id;
+        }
+
+        Ok(())
+    }
+
+    /// Records a newly written chunk, returning its index.
+    pub fn add_chunk(
+        &mut self,
+        action_count: u16,
+        account_keys: &[Pubkey],
+        actions: &[u8],
+    ) -> Result<u16> {
+        let chunk_index = self.chunk_count;
+
+        self.action_count = self
+            .action_count
+            .checked_add(action_count)
+            .ok_or(ErrorCode::TooManyActions)?;
+        self.chunk_count = chunk_index
+            .checked_add(1)
+            .ok_or(ErrorCode::TooManyActions)?;
+        self.actions_hash = ProposalActions::chain_hash(&self.actions_hash, account_keys, actions);
+
+        Ok(chunk_index)
+    }
+
+    /// Forgets the last chunk, restoring the actions hash from before it was added and
+    /// releasing its group ids.
+    pub fn remove_last_chunk(&mut self, chunk: &ProposalActionsData) -> Result<()> {
+        let ids = action_group_ids(chunk.actions())?;
+
+        for (used, id) in self.action_groups.iter_mut().zip(ids.iter()) {
+            *used &= !id;
+        }
+
+        self.action_count -= chunk.header.action_count;
+        self.chunk_count -= 1;
+        self.actions_hash = chunk.header.prev_actions_hash;
+
+        Ok(())
+    }
+
+    pub fn ephemeral_signer_seeds(&self, proposal: Pubkey) -> Result<Vec<EphemeralSignerSeeds>> {
+        self.ephemeral_signer_bumps
+            .iter()
+            .enumerate()
+            .map(|(index, bump)| EphemeralSignerSeeds::new(proposal, index as u8, *bump))
+            .collect()
     }
 }
 
-#[derive(Default, InitSpace, Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+#[derive(Default, InitSpace, Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
 pub enum ProposalStage {
     #[default]
     Draft = 0,
@@ -28,3 +205,64 @@ pub enum ProposalStage {
     Cancelled = 2,
     Failed = 3,
 }
+
+#[derive(Default, InitSpace, Clone, Copy, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub enum ProposalKind {
+    /// Actions are stored on chain in `ProposalActions` chunks.
+    #[default]
+    Stored,
+    /// Only `actions_hash` is stored; the actions are supplied when executing.
+    Committed,
+}
+
+/// Owned signer seeds of `[EPHEMERAL_SIGNER_PREFIX, proposal, index]`, a PDA a proposal
+/// can use wherever an action needs a freshly generated signer.
+pub struct EphemeralSignerSeeds {
+    pub address: Pubkey,
+    proposal: Pubkey,
+    index: [u8; 1],
+    bump: [u8; 1],
+}
+
+impl EphemeralSignerSeeds {
+    pub fn new(proposal: Pubkey, index: u8, bump: u8) -> Result<Self> {
+        let mut signer_seeds = EphemeralSignerSeeds {
+            address: Pubkey::default(),
+            proposal,
+            index: [index],
+            bump: [bump],
+        };
+
+        signer_seeds.address = Pubkey::create_program_address(&signer_seeds.seeds(), &crate::ID)
+            .map_err(|_| ErrorCode::PubkeyDeriveErrorOccurred)?;
+
+        Ok(signer_seeds)
+    }
+
+    pub fn find(proposal: Pubkey, index: u8) -> Result<Self> {
+        let (_, bump) = Pubkey::find_program_address(
+            &[EPHEMERAL_SIGNER_PREFIX.as_ref(), proposal.as_ref(), &[index]],
+            &crate::ID,
+        );
+
+        Self::new(proposal, index, bump)
+    }
+
+    /// Derives the canonical bumps of the first `count` ephemeral signers of `proposal`.
+    pub fn find_all(proposal: Pubkey, count: u8) -> Result<Vec<Self>> {
+        (0..count).map(|index| Self::find(proposal, index)).collect()
+    }
+
+    pub fn bump(&self) -> u8 {
+        self.bump[0]
+    }
+
+    pub fn seeds(&self) -> Vec<&[u8]> {
+        vec![
+            EPHEMERAL_SIGNER_PREFIX.as_ref(),
+            self.proposal.as_ref(),
+            &self.index,
+            &self.bump,
+        ]
+    }
+}
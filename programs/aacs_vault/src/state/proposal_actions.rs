@@ -0,0 +1,102 @@
+use std::mem::size_of;
+
+use anchor_lang::{prelude::*, solana_program::hash::hashv, Discriminator};
+
+use crate::error::ErrorCode;
+
+use super::{Action, ActionViews};
+
+/// Header of one chunk of a proposal's actions, stored at
+/// `[PROPOSAL_ACTIONS_PREFIX, proposal, index]`. The header is followed by the chunk's
+/// account key table and then its actions, Borsh encoded back to back, so handlers can
+/// iterate them in place. Each chunk carries its own key table so it executes on its own.
+#[account(zero_copy)]
+pub struct ProposalActions {
+    pub proposal: Pubkey,
+    /// Actions hash of the proposal before this chunk was added, restored on removal.
+    pub prev_actions_hash: [u8; 32],
+    /// Length in bytes of the encoded actions following the account key table.
+    pub actions_len: u32,
+    pub index: u16,
+    pub account_key_count: u16,
+    pub action_count: u16,
+    pub bump: u8,
+    pub padding: [u8; 1],
+}
+
+/// A chunk's header together with the regions that follow it.
+pub struct ProposalActionsData<'a> {
+    pub header: &'a ProposalActions,
+    /// Deduplicated keys referenced by the actions, passed in this order right after the
+    /// chunk account at execution.
+    pub account_keys: &'a [Pubkey],
+    pub actions: &'a [u8],
+}
+
+impl ProposalActions {
+    pub fn calculate_data_size(account_key_count: usize, actions_size: usize) -> usize {
+        size_of::<ProposalActions>() + 32 * account_key_count + actions_size
+    }
+
+    /// Writes the account key table and the encoded actions after the header of a freshly
+    /// initialized chunk. `data` is the full account data, including the discriminator.
+    pub fn write_regions(
+        data: &mut [u8],
+        account_keys: &[Pubkey],
+        actions: &[Action],
+    ) -> Result<()> {
+        let mut regions = &mut data[8 + size_of::<ProposalActions>()..];
+
+        for pubkey in account_keys {
+            pubkey.serialize(&mut regions)?;
+        }
+
+        for action in actions {
+            action.serialize(&mut regions)?;
+        }
+
+        Ok(())
+    }
+
+    /// Splits the account data of a chunk into its header and regions.
+    pub fn split(data: &[u8]) -> Result<ProposalActionsData<'_>> {
+        require!(
+            data.len() >= 8 + size_of::<ProposalActions>()
+                && data[..8] == *ProposalActions::DISCRIMINATOR,
+            ErrorCode::InvalidProposalActions
+        );
+
+        let (header, regions) = data[8..].split_at(size_of::<ProposalActions>());
+        let header: &ProposalActions = bytemuck::from_bytes(header);
+
+        let account_keys_len = 32 * header.account_key_count as usize;
+        require_eq!(
+            regions.len(),
+            account_keys_len + header.actions_len as usize,
+            ErrorCode::InvalidProposalActions
+        );
+
+        let (account_keys, actions) = regions.split_at(account_keys_len);
+
+        Ok(ProposalActionsData {
+            header,
+            account_keys: bytemuck::cast_slice(account_keys),
+            actions,
+        })
+    }
+
+    /// Folds a chunk into the running hash of the proposal's actions.
+    pub fn chain_hash(
+        actions_hash: &[u8; 32],
+        account_keys: &[Pubkey],
+        actions: &[u8],
+    ) -> [u8; 32] {
+        hashv(&[actions_hash, bytemuck::cast_slice(account_keys), actions]).to_bytes()
+    }
+}
+
+impl<'a> ProposalActionsData<'a> {
+    pub fn actions(&self) -> ActionViews<'a> {
+        ActionViews::new(self.actions, self.header.action_count)
+    }
+}
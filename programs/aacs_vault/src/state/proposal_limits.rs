@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    constants::{
+        DEFAULT_MAX_ACCOUNT_SPECS, DEFAULT_MAX_ACTIONS, DEFAULT_MAX_ACTION_DATA_LEN,
+        DEFAULT_MAX_NAME_LEN,
+    },
+    error::ErrorCode,
+};
+
+use super::Action;
+
+/// Per-vault bounds on the proposals and direct executions its owner submits.
+#[derive(Clone, Copy, InitSpace, AnchorSerialize, AnchorDeserialize)]
+pub struct ProposalLimits {
+    pub max_name_len: u16,
+    /// Maximum number of actions in a proposal, or in a direct execution.
+    pub max_actions: u16,
+    pub max_action_data_len: u32,
+    pub max_account_specs: u16,
+}
+
+impl Default for ProposalLimits {
+    fn default() -> Self {
+        ProposalLimits {
+            max_name_len: DEFAULT_MAX_NAME_LEN,
+            max_actions: DEFAULT_MAX_ACTIONS,
+            max_action_data_len: DEFAULT_MAX_ACTION_DATA_LEN,
+            max_account_specs: DEFAULT_MAX_ACCOUNT_SPECS,
+        }
+    }
+}
+
+impl ProposalLimits {
+    pub fn check_name(&self, name: &str) -> Result<()> {
+        require_gte!(
+            self.max_name_len as usize,
+            name.len(),
+            ErrorCode::NameTooLong
+        );
+
+        Ok(())
+    }
+
+    pub fn check_action_count(&self, action_count: usize) -> Result<()> {
+        require_gte!(
+            self.max_actions as usize,
+            action_count,
+            ErrorCode::TooManyActions
+        );
+
+        Ok(())
+    }
+
+    /// Checks `actions` being added to `existing_action_count` actions already submitted.
+    pub fn check_actions(&self, existing_action_count: u16, actions: &[Action]) -> Result<()> {
+        self.check_action_count(existing_action_count as usize + actions.len())?;
+
+        for action in actions {
+            require_gte!(
+                self.max_action_data_len as usize,
+                action.data.len(),
+                ErrorCode::ActionDataTooLarge
+            );
+            require_gte!(
+                self.max_account_specs as usize,
+                action.account_specs.len(),
+                ErrorCode::TooManyAccountSpecs
+            );
+        }
+
+        Ok(())
+    }
+}
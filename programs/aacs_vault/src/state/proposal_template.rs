@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+
+use crate::error::ErrorCode;
+
+use super::{Action, EphemeralSignerSeeds, ProposalActions, VaultSignerSeeds};
+
+/// An entry of a template's account key table, resolved against the vault and proposal
+/// the template is instantiated for.
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize)]
+pub enum TemplateKey {
+    /// The same account for every vault, such as a program or a mint.
+    Fixed { pubkey: Pubkey },
+    Vault,
+    VaultSigner,
+    SubAccountSigner { index: u8 },
+    /// Associated token account of the vault signer for `mint`.
+    VaultTokenAccount { mint: Pubkey, token_program: Pubkey },
+    /// The proposal instantiated from the template.
+    Proposal,
+    /// Ephemeral signer `index` of the instantiated proposal.
+    EphemeralSigner { index: u8 },
+}
+
+impl TemplateKey {
+    pub fn get_data_size(&self) -> usize {
+        1 + match self {
+            TemplateKey::Fixed { .. } => 32,
+            TemplateKey::SubAccountSigner { .. } | TemplateKey::EphemeralSigner { .. } => 1,
+            TemplateKey::VaultTokenAccount { .. } => 32 + 32,
+            TemplateKey::Vault | TemplateKey::VaultSigner | TemplateKey::Proposal => 0,
+        }
+    }
+
+    pub fn resolve(
+        &self,
+        vault: &Pubkey,
+        vault_signer: &Pubkey,
+        proposal: &Pubkey,
+        ephemeral_signers: &[EphemeralSignerSeeds],
+    ) -> Result<Pubkey> {
+        Ok(match *self {
+            TemplateKey::Fixed { pubkey } => pubkey,
+            TemplateKey::Vault => *vault,
+            TemplateKey::VaultSigner => *vault_signer,
+            TemplateKey::SubAccountSigner { index } => {
+                VaultSignerSeeds::find_sub_account(*vault, index)?.address
+            }
+            TemplateKey::VaultTokenAccount {
+                mint,
+                token_program,
+            } => get_associated_token_address_with_program_id(vault_signer, &mint, &token_program),
+            TemplateKey::Proposal => *proposal,
+            TemplateKey::EphemeralSigner { index } => {
+                ephemeral_signers
+                    .get(index as usize)
+                    .ok_or(ErrorCode::InvalidTemplateKey)?
+                    .address
+            }
+        })
+    }
+}
+
+/// A vault independent list of actions, instantiated as a single chunk proposal for any
+/// vault.
+#[account]
+pub struct ProposalTemplate {
+    pub authority: Pubkey,
+    pub name: String,
+    pub account_keys: Vec<TemplateKey>,
+    pub actions: Vec<Action>,
+}
+
+impl ProposalTemplate {
+    pub fn calculate_data_size(
+        name: &str,
+        account_keys: &[TemplateKey],
+        actions_size: usize,
+    ) -> usize {
+        let account_keys_size: usize = account_keys.iter().map(TemplateKey::get_data_size).sum();
+
+        32 + 4 + name.len() + 4 + account_keys_size + 4 + actions_size
+    }
+
+    /// Size of the `ProposalActions` chunk an instantiated proposal stores the actions in.
+    pub fn chunk_data_size(&self) -> usize {
+        let actions_size = self.actions.iter().map(Action::get_data_size).sum();
+        ProposalActions::calculate_data_size(self.account_keys.len(), actions_size)
+    }
+
+    pub fn resolve_account_keys(
+        &self,
+        vault: &Pubkey,
+        vault_signer: &Pubkey,
+        proposal: &Pubkey,
+        ephemeral_signers: &[EphemeralSignerSeeds],
+    ) -> Result<Vec<Pubkey>> {
+        self.account_keys
+            .iter()
+            .map(|account_key| account_key.resolve(vault, vault_signer, proposal, ephemeral_signers))
+            .collect()
+    }
+}
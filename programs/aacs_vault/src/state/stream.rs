@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Stream {
+    pub vault: Pubkey,
+    pub recipient: Pubkey,
+    /// `None` for a SOL stream escrowed in this account's lamports, otherwise the mint
+    /// escrowed in the stream's ATA.
+    pub mint: Option<Pubkey>,
+    pub stream_id: u64,
+    pub total_amount: u64,
+    pub withdrawn_amount: u64,
+    pub start_date: i64,
+    pub cliff_date: i64,
+    pub end_date: i64,
+    pub bump: u8,
+}
+
+impl Stream {
+    /// Amount released to the recipient at `now`. Nothing vests before the cliff, after
+    /// which the stream vests linearly from `start_date` until `end_date`.
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        if now < self.cliff_date {
+            return 0;
+        }
+
+        if now >= self.end_date {
+            return self.total_amount;
+        }
+
+        let elapsed = (now - self.start_date) as u128;
+        let duration = (self.end_date - self.start_date) as u128;
+
+        (self.total_amount as u128 * elapsed / duration) as u64
+    }
+
+    pub fn withdrawable_amount(&self, now: i64) -> u64 {
+        self.vested_amount(now).saturating_sub(self.withdrawn_amount)
+    }
+}
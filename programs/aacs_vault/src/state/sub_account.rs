@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::{constants::SUB_ACCOUNT_PREFIX, error::ErrorCode};
+
+use super::VaultSignerSeeds;
+
+#[account]
+#[derive(InitSpace)]
+pub struct VaultSubAccount {
+    pub vault: Pubkey,
+    pub index: u8,
+    pub signer_bump: u8,
+    pub created_date: i64,
+}
+
+impl VaultSubAccount {
+    /// Seeds of the signer of sub-account `index` of `vault`. The sub-account must have
+    /// been opened and its `VaultSubAccount` passed among `accounts`.
+    pub fn find_signer(
+        accounts: &[AccountInfo],
+        vault: Pubkey,
+        index: u8,
+    ) -> Result<VaultSignerSeeds> {
+        let (address, _) = Pubkey::find_program_address(
+            &[SUB_ACCOUNT_PREFIX.as_ref(), vault.as_ref(), &[index]],
+            &crate::ID,
+        );
+
+        let Some(account) = accounts.iter().find(|account| account.key == &address) else {
+            msg!("Sub-account {} of the vault was not passed", index);
+            return err!(ErrorCode::SubAccountNotOpened);
+        };
+
+        require_keys_eq!(*account.owner, crate::ID, ErrorCode::SubAccountNotOpened);
+
+        let sub_account = VaultSubAccount::try_deserialize(&mut &account.try_borrow_data()?[..])
+            .map_err(|_| ErrorCode::SubAccountNotOpened)?;
+
+        VaultSignerSeeds::new(vault, Some(index), sub_account.signer_bump)
+    }
+
+    /// Addresses of the signers of the sub-accounts of `vault` whose `VaultSubAccount` is
+    /// among `accounts`, which are the only sub-accounts an execution signs for.
+    pub fn opened_signers(accounts: &[AccountInfo], vault: Pubkey) -> Result<Vec<Pubkey>> {
+        let mut signers: Vec<Pubkey> = vec![];
+
+        for account in accounts {
+            if account.owner != &crate::ID {
+                continue;
+            }
+
+            let data = account.try_borrow_data()?;
+            if !data.starts_with(VaultSubAccount::DISCRIMINATOR) {
+                continue;
+            }
+
+            let sub_account = VaultSubAccount::try_deserialize(&mut &data[..])?;
+            if sub_account.vault != vault {
+                continue;
+            }
+
+            let signer =
+                VaultSignerSeeds::new(vault, Some(sub_account.index), sub_account.signer_bump)?;
+            if !signers.contains(&signer.address) {
+                signers.push(signer.address);
+            }
+        }
+
+        Ok(signers)
+    }
+}
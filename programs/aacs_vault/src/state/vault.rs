@@ -1,9 +1,94 @@
 use anchor_lang::prelude::*;
 
+use crate::{constants::VAULT_SIGNER_PREFIX, error::ErrorCode};
+
+use super::{OutflowCap, ProposalLimits, VaultSubAccount};
+
 #[account]
 #[derive(InitSpace)]
 pub struct Vault {
     pub owner: Pubkey,
     pub created_date: i64,
     pub signer_bump: u8,
+    pub stream_count: u64,
+    pub allowlist_enabled: bool,
+    /// Held while proposal actions are being invoked.
+    pub is_executing: bool,
+    pub proposal_limits: ProposalLimits,
+    /// Applies to every execution of the vault, direct executions included.
+    pub outflow_cap: OutflowCap,
+    /// Nonce the next direct execution must use.
+    pub direct_execution_nonce: u64,
+    /// Set once the vault only executes actions through proposals.
+    pub direct_execution_disabled: bool,
+}
+
+impl Vault {
+    /// Seeds of the main vault signer, or of the sub-account signer when one is given.
+    pub fn signer_seeds(
+        &self,
+        vault: Pubkey,
+        sub_account: Option<&VaultSubAccount>,
+    ) -> Result<VaultSignerSeeds> {
+        match sub_account {
+            Some(sub_account) => {
+                VaultSignerSeeds::new(vault, Some(sub_account.index), sub_account.signer_bump)
+            }
+            None => VaultSignerSeeds::new(vault, None, self.signer_bump),
+        }
+    }
+}
+
+/// Owned signer seeds of a vault signer PDA: `[VAULT_SIGNER_PREFIX, vault]` for the main
+/// signer and `[VAULT_SIGNER_PREFIX, vault, index]` for sub-account signers.
+pub struct VaultSignerSeeds {
+    pub address: Pubkey,
+    vault: Pubkey,
+    index: Option<[u8; 1]>,
+    bump: [u8; 1],
+}
+
+impl VaultSignerSeeds {
+    pub fn new(vault: Pubkey, index: Option<u8>, bump: u8) -> Result<Self> {
+        let mut signer_seeds = VaultSignerSeeds {
+            address: Pubkey::default(),
+            vault,
+            index: index.map(|index| [index]),
+            bump: [bump],
+        };
+
+        signer_seeds.address = Pubkey::create_program_address(&signer_seeds.seeds(), &crate::ID)
+            .map_err(|_| ErrorCode::PubkeyDeriveErrorOccurred)?;
+
+        Ok(signer_seeds)
+    }
+
+    /// Derives the canonical bump of sub-account signer `index`.
+    pub fn find_sub_account(vault: Pubkey, index: u8) -> Result<Self> {
+        let (_, bump) = Pubkey::find_program_address(
+            &[VAULT_SIGNER_PREFIX.as_ref(), vault.as_ref(), &[index]],
+            &crate::ID,
+        );
+
+        Self::new(vault, Some(index), bump)
+    }
+
+    pub fn index(&self) -> Option<u8> {
+        self.index.map(|[index]| index)
+    }
+
+    pub fn bump(&self) -> u8 {
+        self.bump[0]
+    }
+
+    pub fn seeds(&self) -> Vec<&[u8]> {
+        let mut seeds: Vec<&[u8]> = vec![VAULT_SIGNER_PREFIX.as_ref(), self.vault.as_ref()];
+
+        if let Some(index) = self.index.as_ref() {
+            seeds.push(index);
+        }
+
+        seeds.push(&self.bump);
+        seeds
+    }
 }
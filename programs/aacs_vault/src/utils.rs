@@ -0,0 +1,92 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{
+        allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+    },
+};
+
+/// Resizes a program owned account to `new_len`, topping it up from `payer` so it stays
+/// rent exempt. Surplus lamports are left on the account when it shrinks.
+pub fn realloc_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let required_lamports = Rent::get()?.minimum_balance(new_len);
+    let current_lamports = account.lamports();
+
+    if required_lamports > current_lamports {
+        let cpi_context = CpiContext::new(
+            system_program.clone(),
+            Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            },
+        );
+
+        transfer(cpi_context, required_lamports - current_lamports)?;
+    }
+
+    account.realloc(new_len, false)?;
+
+    Ok(())
+}
+
+/// Creates the program owned PDA `account` with `space` bytes, paid for by `payer`. An
+/// account already holding lamports is topped up and taken over instead, as Anchor's
+/// `init` does.
+pub fn create_pda_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+    let signer = &[signer_seeds];
+
+    if current_lamports == 0 {
+        let cpi_context = CpiContext::new_with_signer(
+            system_program.clone(),
+            CreateAccount {
+                from: payer.clone(),
+                to: account.clone(),
+            },
+            signer,
+        );
+
+        return create_account(cpi_context, required_lamports, space as u64, &crate::ID);
+    }
+
+    if required_lamports > current_lamports {
+        let cpi_context = CpiContext::new(
+            system_program.clone(),
+            Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            },
+        );
+
+        transfer(cpi_context, required_lamports - current_lamports)?;
+    }
+
+    let cpi_context = CpiContext::new_with_signer(
+        system_program.clone(),
+        Allocate {
+            account_to_allocate: account.clone(),
+        },
+        signer,
+    );
+    allocate(cpi_context, space as u64)?;
+
+    let cpi_context = CpiContext::new_with_signer(
+        system_program.clone(),
+        Assign {
+            account_to_assign: account.clone(),
+        },
+        signer,
+    );
+    assign(cpi_context, &crate::ID)
+}
@@ -0,0 +1,341 @@
+import { assert } from 'chai';
+import { createHash } from 'crypto';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	assertErrorCode,
+	CompiledActions,
+	createVault,
+	findProposalActions,
+	newVaultFixture,
+	toChunkRemainingAccounts,
+} from './shared';
+
+describe("Action data buffers", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const MEMO_PROGRAM_ID = new anchor.web3.PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+	const WRITE_CHUNK_LEN = 900;
+	const PROGRAM_ALLOWLIST_PREFIX = anchor.utils.bytes.utf8.encode("aacs_program_allowlist");
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey } = vaultFixture;
+
+	/** Creates a buffer and fills it with `data` over several transactions. */
+	async function uploadBuffer(data: Buffer) {
+		const buffer = anchor.web3.Keypair.generate();
+
+		await program.methods
+			.createBuffer({ dataLen: data.length })
+			.accounts({
+				buffer: buffer.publicKey,
+				vault: vaultPublicKey,
+				authority: payerPublicKey,
+			})
+			.signers([payer, buffer])
+			.rpc();
+
+		for (let offset = 0; offset < data.length; offset += WRITE_CHUNK_LEN) {
+			await program.methods
+				.writeBuffer({ offset, bytes: data.subarray(offset, offset + WRITE_CHUNK_LEN) })
+				.accounts({
+					buffer: buffer.publicKey,
+					authority: payerPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+		}
+
+		return buffer.publicKey;
+	}
+
+	/** A memo action reading `data` from `buffer`. */
+	function compileBufferedMemo(buffer: anchor.web3.PublicKey, data: Buffer): CompiledActions {
+		const accountKeys = [MEMO_PROGRAM_ID, buffer];
+
+		return {
+			accountKeys,
+			allAccountKeys: accountKeys,
+			actions: [
+				{
+					programIdIndex: 0,
+					accountSpecs: [],
+					data: Buffer.alloc(0),
+					dataBuffer: {
+						accountIndex: 1,
+						dataLen: data.length,
+						dataHash: [...createHash("sha256").update(data).digest()],
+					},
+					placeholders: [],
+					policy: { atomic: {} },
+				},
+			],
+		};
+	}
+
+	before(async () => {
+		await createVault(program, payer, vaultFixture);
+	});
+
+	describe("flow: createBuffer() -> writeBuffer() -> createProposal() -> attachBuffer() -> executeProposal()", () => {
+		it("executes an action with data larger than a transaction", async () => {
+			const data = Buffer.from("a".repeat(3000));
+			const buffer = await uploadBuffer(data);
+			const compiled = compileBufferedMemo(buffer, data);
+			const proposal = anchor.web3.Keypair.generate();
+
+			await program.methods
+				.createProposal({
+					name: "Large memo",
+					accountKeys: compiled.accountKeys,
+					actions: compiled.actions,
+					ephemeralSignerCount: 0,
+					coSigners: [],
+				})
+				.accountsPartial({
+					proposal: proposal.publicKey,
+					proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.signers([payer, proposal])
+				.rpc();
+
+			try {
+				await program.methods
+					.attachBuffer({
+						dataLen: data.length,
+						dataHash: [...createHash("sha256").update(Buffer.from("b".repeat(3000))).digest()],
+					})
+					.accounts({
+						buffer,
+						proposal: proposal.publicKey,
+						vault: vaultPublicKey,
+						authority: payerPublicKey,
+					})
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Attaching with the wrong hash must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "ActionDataBufferMismatch");
+			}
+
+			await program.methods
+				.attachBuffer({
+					dataLen: data.length,
+					dataHash: [...createHash("sha256").update(data).digest()],
+				})
+				.accounts({
+					buffer,
+					proposal: proposal.publicKey,
+					vault: vaultPublicKey,
+					authority: payerPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+
+			try {
+				await program.methods
+					.writeBuffer({ offset: 0, bytes: Buffer.from("b") })
+					.accounts({
+						buffer,
+						authority: payerPublicKey,
+					})
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Writing an attached buffer must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "ActionDataBufferFrozen");
+			}
+
+			const executionSignature = await program.methods
+				.executeProposal()
+				.accounts({
+					proposal: proposal.publicKey,
+					caller: payerPublicKey,
+				})
+				.remainingAccounts(
+					toChunkRemainingAccounts(program.programId, proposal.publicKey, [compiled]),
+				)
+				.preInstructions([
+					anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 }),
+				])
+				.signers([payer])
+				.rpc();
+
+			console.log("Execution signature:", executionSignature);
+
+			const proposalAccount = await program.account.proposal.fetch(proposal.publicKey);
+			assert(proposalAccount.isExecuted, "Proposal must be executed");
+
+			await program.methods
+				.closeBuffer()
+				.accounts({
+					buffer,
+					proposal: proposal.publicKey,
+					authority: payerPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+
+			assert.isNull(await provider.connection.getAccountInfo(buffer));
+		});
+
+		it("rejects a buffer that was never attached", async () => {
+			const data = Buffer.from("unattached memo");
+			const buffer = await uploadBuffer(data);
+			const compiled = compileBufferedMemo(buffer, data);
+			const proposal = anchor.web3.Keypair.generate();
+
+			await program.methods
+				.createProposal({
+					name: "Unattached memo",
+					accountKeys: compiled.accountKeys,
+					actions: compiled.actions,
+					ephemeralSignerCount: 0,
+					coSigners: [],
+				})
+				.accountsPartial({
+					proposal: proposal.publicKey,
+					proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.signers([payer, proposal])
+				.rpc();
+
+			try {
+				await program.methods
+					.executeProposal()
+					.accounts({
+						proposal: proposal.publicKey,
+						caller: payerPublicKey,
+					})
+					.remainingAccounts(
+						toChunkRemainingAccounts(program.programId, proposal.publicKey, [compiled]),
+					)
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Unattached buffer must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "InvalidActionDataBuffer");
+			}
+		});
+
+		it("rejects a buffer holding other data than the action committed to", async () => {
+			const data = Buffer.from("swapped memo");
+			const buffer = await uploadBuffer(data);
+			const compiled = compileBufferedMemo(buffer, Buffer.from("proposed memo"));
+			const proposal = anchor.web3.Keypair.generate();
+
+			await program.methods
+				.createProposal({
+					name: "Swapped memo",
+					accountKeys: compiled.accountKeys,
+					actions: compiled.actions,
+					ephemeralSignerCount: 0,
+					coSigners: [],
+				})
+				.accountsPartial({
+					proposal: proposal.publicKey,
+					proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.signers([payer, proposal])
+				.rpc();
+
+			await program.methods
+				.attachBuffer({
+					dataLen: data.length,
+					dataHash: [...createHash("sha256").update(data).digest()],
+				})
+				.accounts({
+					buffer,
+					proposal: proposal.publicKey,
+					vault: vaultPublicKey,
+					authority: payerPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+
+			try {
+				await program.methods
+					.executeProposal()
+					.accounts({
+						proposal: proposal.publicKey,
+						caller: payerPublicKey,
+					})
+					.remainingAccounts(
+						toChunkRemainingAccounts(program.programId, proposal.publicKey, [compiled]),
+					)
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Buffer data the action did not commit to must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "ActionDataBufferMismatch");
+			}
+		});
+
+		it("rejects a buffered action of a program off the allowlist", async () => {
+			const [programAllowlist] = anchor.web3.PublicKey.findProgramAddressSync(
+				[PROGRAM_ALLOWLIST_PREFIX, vaultPublicKey.toBuffer()],
+				program.programId,
+			);
+
+			await program.methods
+				.setProgramAllowlist({
+					enabled: true,
+					programs: [{ programId: TOKEN_PROGRAM_ID, discriminators: [] }],
+				})
+				.accountsPartial({
+					owner: payerPublicKey,
+					vault: vaultPublicKey,
+					programAllowlist,
+				})
+				.signers([payer])
+				.rpc();
+
+			const data = Buffer.from("disallowed memo");
+			const buffer = await uploadBuffer(data);
+			const compiled = compileBufferedMemo(buffer, data);
+			const proposal = anchor.web3.Keypair.generate();
+
+			try {
+				await program.methods
+					.createProposal({
+						name: "Disallowed memo",
+						accountKeys: compiled.accountKeys,
+						actions: compiled.actions,
+						ephemeralSignerCount: 0,
+						coSigners: [],
+					})
+					.accountsPartial({
+						proposal: proposal.publicKey,
+						proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+						programAllowlist,
+					})
+					.signers([payer, proposal])
+					.rpc();
+
+				assert.fail("Buffered action of a program off the allowlist must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "ProgramNotAllowed");
+			}
+		});
+	});
+});
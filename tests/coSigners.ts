@@ -0,0 +1,133 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	assertErrorCode,
+	compileActions,
+	createVault,
+	findProposalActions,
+	newVaultFixture,
+	toChunkRemainingAccounts,
+	toRemainingAccounts,
+} from './shared';
+
+describe("Co-signers and external action signers", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+	const coSigner = anchor.web3.Keypair.generate();
+
+	before(async () => {
+		await createVault(program, payer, vaultFixture, 2 * anchor.web3.LAMPORTS_PER_SOL);
+	});
+
+	describe("flow: createProposal() with co-signer -> executeProposal()", () => {
+		it("requires the co-signer to sign the execution", async () => {
+			const proposal = anchor.web3.Keypair.generate();
+			const ix = anchor.web3.SystemProgram.transfer({
+				fromPubkey: vaultSigner,
+				toPubkey: payerPublicKey,
+				lamports: 1000,
+			});
+			const compiled = compileActions([ix]);
+
+			await program.methods
+				.createProposal({
+					name: "Co-signed transfer",
+					accountKeys: compiled.accountKeys,
+					actions: compiled.actions,
+					ephemeralSignerCount: 0,
+					coSigners: [coSigner.publicKey],
+				})
+				.accountsPartial({
+					proposal: proposal.publicKey,
+					proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.signers([payer, proposal])
+				.rpc();
+
+			const remainingAccounts = toChunkRemainingAccounts(program.programId, proposal.publicKey, [
+				compiled,
+			]);
+
+			try {
+				await program.methods
+					.executeProposal()
+					.accounts({
+						proposal: proposal.publicKey,
+						caller: payerPublicKey,
+					})
+					.remainingAccounts(remainingAccounts)
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Execution without the co-signer must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "MissingCoSigner");
+			}
+
+			const executionSignature = await program.methods
+				.executeProposal()
+				.accounts({
+					proposal: proposal.publicKey,
+					caller: payerPublicKey,
+				})
+				.remainingAccounts([
+					...remainingAccounts,
+					{ pubkey: coSigner.publicKey, isSigner: true, isWritable: false },
+				])
+				.signers([payer, coSigner])
+				.rpc();
+
+			console.log("Execution signature:", executionSignature);
+
+			const proposalAccount = await program.account.proposal.fetch(proposal.publicKey);
+			assert(proposalAccount.isExecuted, "Proposal must be executed");
+		});
+	});
+
+	describe("flow: executeProposalDirect() with an external signer", () => {
+		it("names the missing signer before invoking anything", async () => {
+			const external = anchor.web3.Keypair.generate();
+			const ix = anchor.web3.SystemProgram.transfer({
+				fromPubkey: external.publicKey,
+				toPubkey: payerPublicKey,
+				lamports: 1000,
+			});
+			const compiled = compileActions([ix]);
+
+			try {
+				await program.methods
+					.executeProposalDirect({ nonce: new anchor.BN(0), actions: compiled.actions })
+					.accounts({
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.remainingAccounts(toRemainingAccounts(compiled.allAccountKeys, compiled.actions))
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Missing external signer must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "MissingActionSigner");
+				assert(
+					(error as anchor.AnchorError).logs.some((log) =>
+						log.includes(`Action 0 requires ${external.publicKey.toString()} to sign`),
+					),
+					"Error log must name the action index and signer",
+				);
+			}
+		});
+	});
+});
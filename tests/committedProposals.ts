@@ -0,0 +1,267 @@
+import { assert } from 'chai';
+import { createHash } from 'crypto';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	assertErrorCode,
+	calculateActionsSize,
+	calculateProposalSize,
+	compileActions,
+	createVault,
+	hashCommittedActions,
+	newVaultFixture,
+	toRemainingAccounts,
+} from './shared';
+
+describe("Hash-committed proposals", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+	const BUFFER_LEN = 600;
+	const recipients = [...Array(4)].map(() => anchor.web3.Keypair.generate().publicKey);
+
+	function compilePayouts(lamports: number) {
+		return compileActions(
+			recipients.map((toPubkey) =>
+				anchor.web3.SystemProgram.transfer({
+					fromPubkey: vaultSigner,
+					toPubkey,
+					lamports,
+				}),
+			),
+		);
+	}
+
+	async function createCommittedProposal(
+		name: string,
+		compiled: ReturnType<typeof compileActions>,
+		actionsSize = 32 * compiled.accountKeys.length + calculateActionsSize(compiled.actions),
+	) {
+		const proposal = anchor.web3.Keypair.generate();
+
+		await program.methods
+			.createCommittedProposal({
+				name,
+				actionsHash: hashCommittedActions(program, compiled.accountKeys, compiled.actions),
+				actionCount: compiled.actions.length,
+				actionsSize,
+				ephemeralSignerCount: 0,
+				coSigners: [],
+			})
+			.accountsPartial({
+				proposal: proposal.publicKey,
+				vault: vaultPublicKey,
+				proposer: payerPublicKey,
+			})
+			.signers([payer, proposal])
+			.rpc();
+
+		return proposal.publicKey;
+	}
+
+	before(async () => {
+		await createVault(program, payer, vaultFixture, 2 * anchor.web3.LAMPORTS_PER_SOL);
+	});
+
+	describe("flow: createCommittedProposal() -> executeCommittedProposal()", () => {
+		it("stores only the digest and runs the supplied actions", async () => {
+			const lamports = anchor.web3.LAMPORTS_PER_SOL / 100;
+			const compiled = compilePayouts(lamports);
+			const name = "Committed payouts";
+			const proposal = await createCommittedProposal(name, compiled);
+
+			const proposalInfo = await provider.connection.getAccountInfo(proposal);
+			assert.equal(proposalInfo!.data.length, 8 + calculateProposalSize(name));
+
+			const proposalAccount = await program.account.proposal.fetch(proposal);
+			assert.deepEqual(proposalAccount.kind, { committed: {} });
+			assert.equal(proposalAccount.actionCount, recipients.length);
+			assert.equal(proposalAccount.chunkCount, 0);
+
+			const executionSignature = await program.methods
+				.executeCommittedProposal({
+					accountKeys: compiled.accountKeys,
+					actions: compiled.actions,
+					bufferedActions: null,
+				})
+				.accounts({
+					proposal,
+					caller: payerPublicKey,
+				})
+				.remainingAccounts(toRemainingAccounts(compiled.allAccountKeys, compiled.actions))
+				.signers([payer])
+				.rpc();
+
+			console.log("Execution signature:", executionSignature);
+
+			const executedProposal = await program.account.proposal.fetch(proposal);
+			assert(executedProposal.isExecuted, "Proposal must be executed");
+
+			for (const recipient of recipients) {
+				assert.equal(await provider.connection.getBalance(recipient), lamports);
+			}
+		});
+
+		it("rejects actions that do not match the digest", async () => {
+			const compiled = compilePayouts(anchor.web3.LAMPORTS_PER_SOL / 100);
+			const proposal = await createCommittedProposal("Tampered payouts", compiled);
+			const tampered = compilePayouts(anchor.web3.LAMPORTS_PER_SOL / 10);
+
+			try {
+				await program.methods
+					.executeCommittedProposal({
+						accountKeys: tampered.accountKeys,
+						actions: tampered.actions,
+						bufferedActions: null,
+					})
+					.accounts({
+						proposal,
+						caller: payerPublicKey,
+					})
+					.remainingAccounts(toRemainingAccounts(tampered.allAccountKeys, tampered.actions))
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Tampered actions must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "ActionsHashMismatch");
+			}
+		});
+
+		it("rejects actions of another size than the proposal committed to", async () => {
+			const compiled = compilePayouts(1000);
+			const actionsSize = 32 * compiled.accountKeys.length + calculateActionsSize(compiled.actions);
+			const proposal = await createCommittedProposal("Resized payouts", compiled, actionsSize + 1);
+
+			try {
+				await program.methods
+					.executeCommittedProposal({
+						accountKeys: compiled.accountKeys,
+						actions: compiled.actions,
+						bufferedActions: null,
+					})
+					.accounts({
+						proposal,
+						caller: payerPublicKey,
+					})
+					.remainingAccounts(toRemainingAccounts(compiled.allAccountKeys, compiled.actions))
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Actions of another size must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "CommittedActionsSizeMismatch");
+			}
+		});
+
+		it("loads actions too large for one transaction from buffers", async () => {
+			const lamports = 1000;
+			const batchRecipients = [...Array(16)].map(() => anchor.web3.Keypair.generate().publicKey);
+			const compiled = compileActions(
+				batchRecipients.map((toPubkey) =>
+					anchor.web3.SystemProgram.transfer({ fromPubkey: vaultSigner, toPubkey, lamports }),
+				),
+			);
+			const proposal = await createCommittedProposal("Buffered payouts", compiled);
+
+			const payload = Buffer.concat([
+				...compiled.accountKeys.map((pubkey) => pubkey.toBuffer()),
+				...compiled.actions.map((action) => program.coder.types.encode("action", action)),
+			]);
+			const buffers: anchor.web3.PublicKey[] = [];
+
+			for (let offset = 0; offset < payload.length; offset += BUFFER_LEN) {
+				const data = payload.subarray(offset, offset + BUFFER_LEN);
+				const buffer = anchor.web3.Keypair.generate();
+
+				await program.methods
+					.createBuffer({ dataLen: data.length })
+					.accounts({
+						buffer: buffer.publicKey,
+						vault: vaultPublicKey,
+						authority: payerPublicKey,
+					})
+					.signers([payer, buffer])
+					.rpc();
+				await program.methods
+					.writeBuffer({ offset: 0, bytes: data })
+					.accounts({
+						buffer: buffer.publicKey,
+						authority: payerPublicKey,
+					})
+					.signers([payer])
+					.rpc();
+				await program.methods
+					.attachBuffer({
+						dataLen: data.length,
+						dataHash: [...createHash("sha256").update(data).digest()],
+					})
+					.accounts({
+						buffer: buffer.publicKey,
+						proposal,
+						vault: vaultPublicKey,
+						authority: payerPublicKey,
+					})
+					.signers([payer])
+					.rpc();
+
+				buffers.push(buffer.publicKey);
+			}
+
+			assert.isAbove(buffers.length, 1);
+
+			await program.methods
+				.executeCommittedProposal({
+					accountKeys: [],
+					actions: [],
+					bufferedActions: {
+						bufferCount: buffers.length,
+						accountKeyCount: compiled.accountKeys.length,
+					},
+				})
+				.accounts({
+					proposal,
+					caller: payerPublicKey,
+				})
+				.remainingAccounts([
+					...buffers.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false })),
+					...toRemainingAccounts(compiled.allAccountKeys, compiled.actions),
+				])
+				.signers([payer])
+				.rpc();
+
+			for (const recipient of batchRecipients) {
+				assert.equal(await provider.connection.getBalance(recipient), lamports);
+			}
+		});
+
+		it("cannot be executed as a stored proposal", async () => {
+			const compiled = compilePayouts(1000);
+			const proposal = await createCommittedProposal("Wrong kind", compiled);
+
+			try {
+				await program.methods
+					.executeProposal()
+					.accounts({
+						proposal,
+						caller: payerPublicKey,
+					})
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Committed proposal must not run as a stored proposal");
+			} catch (error) {
+				assertErrorCode(error, "WrongProposalKind");
+			}
+		});
+	});
+});
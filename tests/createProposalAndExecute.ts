@@ -5,8 +5,10 @@ import { Program } from '@coral-xyz/anchor';
 
 import { AacsVault } from '../target/types/aacs_vault';
 import {
-  calculateProposalSize,
+  compileActions,
+  findProposalActions,
   getBlockTime,
+  toChunkRemainingAccounts,
 } from './shared';
 
 describe("Create proposal and execute proposal flow", () => {
@@ -16,6 +18,7 @@ describe("Create proposal and execute proposal flow", () => {
 	const program = anchor.workspace.aacsVault as Program<AacsVault>;
 
 	const VAULT_SIGNER_PREFIX = anchor.utils.bytes.utf8.encode("aacs_vault_signer");
+	const EXECUTION_RESULT_PREFIX = anchor.utils.bytes.utf8.encode("aacs_proposal_exec_results");
 	const ONE_MONTH = 30 * 24 * 60 * 60;
 
 	const payer = provider.wallet!.payer!;
@@ -60,29 +63,25 @@ describe("Create proposal and execute proposal flow", () => {
 				lamports: withdrawAmount,
 			});
 
-			const proposalAccountSize = calculateProposalSize([ix]);
-			// console.log("Proposal Account Size: ", proposalAccountSize);
+			const compiled = compileActions([ix]);
 
 			const proposalParams = {
 				name: "Proposal 0001",
-				actions: [
-					{
-						accountSpecs: ix.keys,
-						data: ix.data,
-						programId: ix.programId,
-					},
-				],
-				proposalAccountSize,
+				accountKeys: compiled.accountKeys,
+				actions: compiled.actions,
 			};
 			const timeA = await getBlockTime(provider.connection);
 			const createProposalSignature = await program.methods
 				.createProposal({
 					name: proposalParams.name,
+					accountKeys: proposalParams.accountKeys,
 					actions: proposalParams.actions,
-					proposalAccountSize: proposalParams.proposalAccountSize,
+					ephemeralSignerCount: 0,
+					coSigners: [],
 				})
-				.accounts({
+				.accountsPartial({
 					proposal: proposals[0].publicKey,
+					proposalActions: findProposalActions(program.programId, proposals[0].publicKey, 0),
 					vault: vaultPublicKey,
 					proposer: payerPublicKey,
 				})
@@ -121,17 +120,9 @@ describe("Create proposal and execute proposal flow", () => {
 					proposal: proposals[0].publicKey,
 					caller: payerPublicKey,
 				})
-				.remainingAccounts([
-					...ix.keys.map((key) => ({
-						...key,
-						isSigner: key.pubkey.equals(vaultSigner) ? false : key.isSigner,
-					})),
-					{
-						pubkey: ix.programId,
-						isSigner: false,
-						isWritable: false,
-					},
-				])
+				.remainingAccounts(
+					toChunkRemainingAccounts(program.programId, proposals[0].publicKey, [compiled]),
+				)
 				.signers([payer])
 				.rpc();
 
@@ -149,6 +140,24 @@ describe("Create proposal and execute proposal flow", () => {
 				JSON.stringify(proposalAccount.proposalStage) === JSON.stringify({ completed: {} }),
 				"Proposal must be marked as executed after execution",
 			);
+
+			const [executionResult] = anchor.web3.PublicKey.findProgramAddressSync(
+				[EXECUTION_RESULT_PREFIX, proposals[0].publicKey.toBuffer()],
+				program.programId,
+			);
+			const executionResultAccount = await program.account.proposalExecutionResult.fetch(
+				executionResult,
+			);
+
+			assert(
+				executionResultAccount.proposal.equals(proposals[0].publicKey),
+				"Execution result does not belong to the proposal",
+			);
+			assert(
+				executionResultAccount.results.length === 0,
+				"System transfer must not produce return data",
+			);
+			assert(!executionResultAccount.truncated, "Execution result must hold every result");
 		});
 	});
 });
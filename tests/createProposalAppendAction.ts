@@ -5,8 +5,9 @@ import { Program } from '@coral-xyz/anchor';
 
 import { AacsVault } from '../target/types/aacs_vault';
 import {
-  calculateActionsSize,
   calculateProposalSize,
+  compileActions,
+  findProposalActions,
   getBlockTime,
 } from './shared';
 
@@ -30,7 +31,7 @@ describe("Create proposal and append action flow", () => {
 
 	const proposals = [anchor.web3.Keypair.generate()];
 
-	describe("flow: createVault() -> createProposal() -> appendAction()", () => {
+	describe("flow: createVault() -> createProposal() -> appendAction() -> removeActions()", () => {
 		it("works as expected", async () => {
 			// Add your test here.
 			const createVaultSignature = await program.methods
@@ -61,29 +62,25 @@ describe("Create proposal and append action flow", () => {
 				lamports: withdrawAmount,
 			});
 
-			const proposalAccountSize = calculateProposalSize([ix]);
-			// console.log("Proposal Account Size: ", proposalAccountSize);
+			const compiled = compileActions([ix]);
 
 			const proposalParams = {
 				name: "Proposal 0001",
-				actions: [
-					{
-						accountSpecs: ix.keys,
-						data: ix.data,
-						programId: ix.programId,
-					},
-				],
-				proposalAccountSize,
+				accountKeys: compiled.accountKeys,
+				actions: compiled.actions,
 			};
 			const timeA = await getBlockTime(provider.connection);
 			const createProposalSignature = await program.methods
 				.createProposal({
 					name: proposalParams.name,
+					accountKeys: proposalParams.accountKeys,
 					actions: proposalParams.actions,
-					proposalAccountSize: proposalParams.proposalAccountSize,
+					ephemeralSignerCount: 0,
+					coSigners: [],
 				})
-				.accounts({
+				.accountsPartial({
 					proposal: proposals[0].publicKey,
+					proposalActions: findProposalActions(program.programId, proposals[0].publicKey, 0),
 					vault: vaultPublicKey,
 					proposer: payerPublicKey,
 				})
@@ -119,7 +116,8 @@ describe("Create proposal and append action flow", () => {
 				proposalAccount.vault.equals(vaultPublicKey),
 				"Proposal does not belong to right vault",
 			);
-			assert((proposalAccount.actions.length = 1), "Proposal must have 1 action");
+			assert(proposalAccount.actionCount === 1, "Proposal must have 1 action");
+			const actionsHashAfterCreate = proposalAccount.actionsHash;
 
 			const proposalAccountInfo = await provider.connection.getAccountInfo(
 				proposals[0].publicKey,
@@ -128,10 +126,9 @@ describe("Create proposal and append action flow", () => {
 			assert(proposalAccountInfo, "Proposal account is not initialized.");
 			const fetchedProposalAccountSize = proposalAccountInfo.data.length;
 
-			console.log(
-				"set proposal size: %d & fetched proposal size: %d",
-				proposalAccountSize,
-				fetchedProposalAccountSize,
+			assert(
+				fetchedProposalAccountSize === 8 + calculateProposalSize(proposalParams.name),
+				"Proposal account must be allocated to its exact size",
 			);
 
 			const ix2 = anchor.web3.SystemProgram.transfer({
@@ -140,22 +137,17 @@ describe("Create proposal and append action flow", () => {
 				lamports: withdrawAmount,
 			});
 
-			const proposalAccountSize2 =
-				calculateActionsSize([ix]) + fetchedProposalAccountSize - 8; // Subtract Discriminator
+			const compiled2 = compileActions([ix2]);
 
 			const appendActionSignature = await program.methods
 				.appendActions({
-					actions: [
-						{
-							accountSpecs: ix2.keys,
-							data: ix2.data,
-							programId: ix2.programId,
-						},
-					],
-					proposalAccountSize: proposalAccountSize2,
+					accountKeys: compiled2.accountKeys,
+					actions: compiled2.actions,
 				})
-				.accounts({
+				.accountsPartial({
 					proposal: proposals[0].publicKey,
+					proposalActions: findProposalActions(program.programId, proposals[0].publicKey, 1),
+					vault: vaultPublicKey,
 					proposer: payerPublicKey,
 				})
 				.signers([payer])
@@ -183,7 +175,34 @@ describe("Create proposal and append action flow", () => {
 				proposalAccount.vault.equals(vaultPublicKey),
 				"Proposal does not belong to right vault",
 			);
-			assert((proposalAccount.actions.length = 2), "Proposal must have 2 action");
+			assert(proposalAccount.actionCount === 2, "Proposal must have 2 actions");
+			assert(proposalAccount.chunkCount === 2, "Proposal must have 2 action chunks");
+
+			const secondChunk = findProposalActions(program.programId, proposals[0].publicKey, 1);
+			const removeActionsSignature = await program.methods
+				.removeActions()
+				.accountsPartial({
+					proposal: proposals[0].publicKey,
+					proposalActions: secondChunk,
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+
+			console.log("Remove Actions Signature: ", removeActionsSignature);
+
+			const proposalAfterRemoval = await program.account.proposal.fetch(proposals[0].publicKey);
+			assert(proposalAfterRemoval.actionCount === 1, "Proposal must have 1 action after removal");
+			assert(proposalAfterRemoval.chunkCount === 1, "Proposal must have 1 chunk after removal");
+			assert(
+				Buffer.from(proposalAfterRemoval.actionsHash).equals(Buffer.from(actionsHashAfterCreate)),
+				"Actions hash must be restored after removal",
+			);
+			assert(
+				(await provider.connection.getAccountInfo(secondChunk)) == null,
+				"Removed chunk must be closed",
+			);
 		});
 	});
 });
@@ -5,9 +5,12 @@ import { Program } from '@coral-xyz/anchor';
 
 import { AacsVault } from '../target/types/aacs_vault';
 import {
-  calculateActionsSize,
   calculateProposalSize,
+  compileActions,
+  findProposalActions,
   getBlockTime,
+  toChunkRemainingAccounts,
+  toRemainingAccounts,
 } from './shared';
 
 describe("Create proposal, append action and execute proposal flow", () => {
@@ -28,7 +31,7 @@ describe("Create proposal, append action and execute proposal flow", () => {
 		program.programId,
 	);
 
-	const proposals = [anchor.web3.Keypair.generate()];
+	const proposals = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
 
 	describe("flow: createVault() -> createProposal() -> executeProposal()", () => {
 		it("works as expected", async () => {
@@ -61,29 +64,25 @@ describe("Create proposal, append action and execute proposal flow", () => {
 				lamports: withdrawAmount,
 			});
 
-			const proposalAccountSize = calculateProposalSize([ix]);
-			// console.log("Proposal Account Size: ", proposalAccountSize);
+			const compiled = compileActions([ix]);
 
 			const proposalParams = {
 				name: "Proposal 0001",
-				actions: [
-					{
-						accountSpecs: ix.keys,
-						data: ix.data,
-						programId: ix.programId,
-					},
-				],
-				proposalAccountSize,
+				accountKeys: compiled.accountKeys,
+				actions: compiled.actions,
 			};
 			const timeA = await getBlockTime(provider.connection);
 			const createProposalSignature = await program.methods
 				.createProposal({
 					name: proposalParams.name,
+					accountKeys: proposalParams.accountKeys,
 					actions: proposalParams.actions,
-					proposalAccountSize: proposalParams.proposalAccountSize,
+					ephemeralSignerCount: 0,
+					coSigners: [],
 				})
-				.accounts({
+				.accountsPartial({
 					proposal: proposals[0].publicKey,
+					proposalActions: findProposalActions(program.programId, proposals[0].publicKey, 0),
 					vault: vaultPublicKey,
 					proposer: payerPublicKey,
 				})
@@ -119,7 +118,7 @@ describe("Create proposal, append action and execute proposal flow", () => {
 				proposalAccount.vault.equals(vaultPublicKey),
 				"Proposal does not belong to right vault",
 			);
-			assert((proposalAccount.actions.length = 1), "Proposal must have 1 action");
+			assert(proposalAccount.actionCount === 1, "Proposal must have 1 action");
 
 			const proposalAccountInfo = await provider.connection.getAccountInfo(
 				proposals[0].publicKey,
@@ -128,10 +127,9 @@ describe("Create proposal, append action and execute proposal flow", () => {
 			assert(proposalAccountInfo, "Proposal account is not initialized.");
 			const fetchedProposalAccountSize = proposalAccountInfo.data.length;
 
-			console.log(
-				"set proposal size: %d & fetched proposal size: %d",
-				proposalAccountSize,
-				fetchedProposalAccountSize,
+			assert(
+				fetchedProposalAccountSize === 8 + calculateProposalSize(proposalParams.name),
+				"Proposal account must be allocated to its exact size",
 			);
 
 			const ix2 = anchor.web3.SystemProgram.transfer({
@@ -140,22 +138,17 @@ describe("Create proposal, append action and execute proposal flow", () => {
 				lamports: withdrawAmount,
 			});
 
-			const proposalAccountSize2 =
-				calculateActionsSize([ix]) + fetchedProposalAccountSize - 8; // Subtract Discriminator
+			const compiled2 = compileActions([ix2]);
 
 			const appendActionSignature = await program.methods
 				.appendActions({
-					actions: [
-						{
-							accountSpecs: ix2.keys,
-							data: ix2.data,
-							programId: ix2.programId,
-						},
-					],
-					proposalAccountSize: proposalAccountSize2,
+					accountKeys: compiled2.accountKeys,
+					actions: compiled2.actions,
 				})
-				.accounts({
+				.accountsPartial({
 					proposal: proposals[0].publicKey,
+					proposalActions: findProposalActions(program.programId, proposals[0].publicKey, 1),
+					vault: vaultPublicKey,
 					proposer: payerPublicKey,
 				})
 				.signers([payer])
@@ -183,7 +176,8 @@ describe("Create proposal, append action and execute proposal flow", () => {
 				proposalAccount.vault.equals(vaultPublicKey),
 				"Proposal does not belong to right vault",
 			);
-			assert((proposalAccount.actions.length = 2), "Proposal must have 2 action");
+			assert(proposalAccount.actionCount === 2, "Proposal must have 2 actions");
+			assert(proposalAccount.chunkCount === 2, "Proposal must have 2 action chunks");
 
 			const executionSignature = await program.methods
 				.executeProposal()
@@ -191,17 +185,9 @@ describe("Create proposal, append action and execute proposal flow", () => {
 					proposal: proposals[0].publicKey,
 					caller: payerPublicKey,
 				})
-				.remainingAccounts([
-					...ix.keys.map((key) => ({
-						...key,
-						isSigner: key.pubkey.equals(vaultSigner) ? false : key.isSigner,
-					})),
-					{
-						pubkey: ix.programId,
-						isSigner: false,
-						isWritable: false,
-					},
-				])
+				.remainingAccounts(
+					toChunkRemainingAccounts(program.programId, proposals[0].publicKey, [compiled, compiled2]),
+				)
 				.signers([payer])
 				.rpc();
 
@@ -221,4 +207,93 @@ describe("Create proposal, append action and execute proposal flow", () => {
 			);
 		});
 	});
+
+	describe("flow: createProposal() -> appendActions() -> executeProposal() with chunks out of order", () => {
+		it("rejects the proposal before invoking any action", async () => {
+			const withdrawAmount = 1 * anchor.web3.LAMPORTS_PER_SOL;
+			const recipient = anchor.web3.Keypair.generate().publicKey;
+
+			const compiled = compileActions([
+				anchor.web3.SystemProgram.transfer({
+					fromPubkey: vaultSigner,
+					toPubkey: recipient,
+					lamports: withdrawAmount,
+				}),
+			]);
+			const compiled2 = compileActions([
+				anchor.web3.SystemProgram.transfer({
+					fromPubkey: vaultSigner,
+					toPubkey: recipient,
+					lamports: withdrawAmount,
+				}),
+			]);
+
+			await program.methods
+				.createProposal({
+					name: "Proposal 0002",
+					accountKeys: compiled.accountKeys,
+					actions: compiled.actions,
+					ephemeralSignerCount: 0,
+					coSigners: [],
+				})
+				.accountsPartial({
+					proposal: proposals[1].publicKey,
+					proposalActions: findProposalActions(program.programId, proposals[1].publicKey, 0),
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.signers([payer, proposals[1]])
+				.rpc();
+
+			await program.methods
+				.appendActions({
+					accountKeys: compiled2.accountKeys,
+					actions: compiled2.actions,
+				})
+				.accountsPartial({
+					proposal: proposals[1].publicKey,
+					proposalActions: findProposalActions(program.programId, proposals[1].publicKey, 1),
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+
+			const [chunk0, ...keys0] = toChunkRemainingAccounts(
+				program.programId,
+				proposals[1].publicKey,
+				[compiled],
+			);
+			const keys1 = toRemainingAccounts(compiled2.allAccountKeys, compiled2.actions);
+			const chunk1 = {
+				pubkey: findProposalActions(program.programId, proposals[1].publicKey, 1),
+				isSigner: false,
+				isWritable: false,
+			};
+
+			let failed = false;
+			try {
+				await program.methods
+					.executeProposal()
+					.accounts({
+						proposal: proposals[1].publicKey,
+						caller: payerPublicKey,
+					})
+					.remainingAccounts([chunk1, ...keys1, chunk0, ...keys0])
+					.signers([payer])
+					.rpc();
+			} catch (err) {
+				failed = true;
+				assert.strictEqual(err.error?.errorCode?.code, "InvalidProposalActions");
+			}
+			assert(failed, "Execution with chunks out of order must fail");
+
+			const recipientBalance = await provider.connection.getBalance(recipient, "confirmed");
+			assert.strictEqual(recipientBalance, 0, "No action must have been invoked");
+
+			const proposalAccount = await program.account.proposal.fetch(proposals[1].publicKey);
+			assert(!proposalAccount.isExecuted, "Proposal must stay unexecuted");
+			assert(JSON.stringify(proposalAccount.proposalStage) == JSON.stringify({ draft: {} }));
+		});
+	});
 });
@@ -45,9 +45,10 @@ describe("Deposit, and withdraw SOL flow", () => {
 			const depositAmount = 10 * anchor.web3.LAMPORTS_PER_SOL;
 			const depositSolSignature = await program.methods
 				.depositSol({ amount: new anchor.BN(depositAmount) })
-				.accounts({
+				.accountsPartial({
 					depositor: payerPublicKey,
 					vault: vaultPublicKey,
+					vaultSigner,
 				})
 				.signers([payer])
 				.rpc();
@@ -69,9 +70,10 @@ describe("Deposit, and withdraw SOL flow", () => {
 			const withdrawAmount = 4 * anchor.web3.LAMPORTS_PER_SOL;
 			const withdrawSolSignature = await program.methods
 				.withdrawSol({ amount: new anchor.BN(withdrawAmount) })
-				.accounts({
+				.accountsPartial({
 					vault: vaultPublicKey,
 					withdrawer: payerPublicKey,
+					vaultSigner,
 				})
 				.signers([payer])
 				.rpc();
@@ -116,7 +116,7 @@ describe("Deposit and withdraw Token flow", () => {
 					vault: vaultPublicKey,
 					withdrawer: payerPublicKey,
 					tokenMint: mint,
-					// vaultSigner: vaultSigner,
+					vaultSigner: vaultSigner,
 					// vaultSignerTokenAccount: vaultSignerAta,
 					// withdrawerTokenAccount: payerAta,
 				})
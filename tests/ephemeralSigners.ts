@@ -0,0 +1,108 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	compileActions,
+	findProposalActions,
+	toChunkRemainingAccounts,
+} from './shared';
+
+describe("Ephemeral signer flow", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const VAULT_SIGNER_PREFIX = anchor.utils.bytes.utf8.encode("aacs_vault_signer");
+	const EPHEMERAL_SIGNER_PREFIX = anchor.utils.bytes.utf8.encode("aacs_ephemeral_signer");
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vault = anchor.web3.Keypair.generate();
+	const vaultPublicKey = vault.publicKey;
+	const [vaultSigner, vaultBump] = anchor.web3.PublicKey.findProgramAddressSync(
+		[VAULT_SIGNER_PREFIX, vaultPublicKey.toBuffer()],
+		program.programId,
+	);
+
+	const proposal = anchor.web3.Keypair.generate();
+	const [ephemeralSigner] = anchor.web3.PublicKey.findProgramAddressSync(
+		[EPHEMERAL_SIGNER_PREFIX, proposal.publicKey.toBuffer(), Buffer.from([0])],
+		program.programId,
+	);
+
+	describe("flow: createVault() -> createProposal() with ephemeral signer -> executeProposal()", () => {
+		it("creates an account owned by the vault signer", async () => {
+			await program.methods
+				.createVault({ owner: payerPublicKey, signerBump: vaultBump })
+				.accounts({
+					vault: vaultPublicKey,
+				})
+				.postInstructions([
+					anchor.web3.SystemProgram.transfer({
+						fromPubkey: payerPublicKey,
+						toPubkey: vaultSigner,
+						lamports: 10 * anchor.web3.LAMPORTS_PER_SOL,
+					}),
+				])
+				.signers([payer, vault])
+				.rpc();
+
+			const space = 64;
+			const lamports = await provider.connection.getMinimumBalanceForRentExemption(space);
+			const ix = anchor.web3.SystemProgram.createAccount({
+				fromPubkey: vaultSigner,
+				newAccountPubkey: ephemeralSigner,
+				lamports,
+				space,
+				programId: anchor.web3.SystemProgram.programId,
+			});
+
+			const compiled = compileActions([ix]);
+
+			const createProposalSignature = await program.methods
+				.createProposal({
+					name: "Create account",
+					accountKeys: compiled.accountKeys,
+					actions: compiled.actions,
+					ephemeralSignerCount: 1,
+					coSigners: [],
+				})
+				.accountsPartial({
+					proposal: proposal.publicKey,
+					proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.signers([payer, proposal])
+				.rpc();
+
+			console.log("Create Proposal Signature: ", createProposalSignature);
+
+			const proposalAccount = await program.account.proposal.fetch(proposal.publicKey);
+			assert(
+				proposalAccount.ephemeralSignerBumps.length === 1,
+				"Proposal must record one ephemeral signer bump",
+			);
+
+			const executionSignature = await program.methods
+				.executeProposal()
+				.accounts({
+					proposal: proposal.publicKey,
+					caller: payerPublicKey,
+				})
+				.remainingAccounts(toChunkRemainingAccounts(program.programId, proposal.publicKey, [compiled]))
+				.signers([payer])
+				.rpc();
+
+			console.log("Execution signature:", executionSignature);
+
+			const createdAccount = await provider.connection.getAccountInfo(ephemeralSigner);
+			assert(createdAccount != null, "Ephemeral signer account must be created");
+			assert(createdAccount.data.length === space, "Created account size does not match");
+		});
+	});
+});
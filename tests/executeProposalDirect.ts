@@ -4,7 +4,7 @@ import * as anchor from '@coral-xyz/anchor';
 import { Program } from '@coral-xyz/anchor';
 
 import { AacsVault } from '../target/types/aacs_vault';
-import { sleep } from './shared';
+import { compileActions, sleep, toRemainingAccounts } from './shared';
 
 describe("Create vault, deposit, and execute proposal direct", () => {
 	// Configure the client to use the local cluster.
@@ -13,6 +13,7 @@ describe("Create vault, deposit, and execute proposal direct", () => {
 	const program = anchor.workspace.aacsVault as Program<AacsVault>;
 
 	const VAULT_SIGNER_PREFIX = anchor.utils.bytes.utf8.encode("aacs_vault_signer");
+	const DIRECT_EXECUTION_RECORD_PREFIX = anchor.utils.bytes.utf8.encode("aacs_direct_execution");
 	const payer = provider.wallet!.payer!;
 	const payerPublicKey = payer.publicKey;
 	const vault = anchor.web3.Keypair.generate();
@@ -22,6 +23,28 @@ describe("Create vault, deposit, and execute proposal direct", () => {
 		program.programId,
 	);
 
+	function findDirectExecutionRecord(nonce: number) {
+		const [record] = anchor.web3.PublicKey.findProgramAddressSync(
+			[
+				DIRECT_EXECUTION_RECORD_PREFIX,
+				vaultPublicKey.toBuffer(),
+				new anchor.BN(nonce).toArrayLike(Buffer, "le", 8),
+			],
+			program.programId,
+		);
+		return record;
+	}
+
+	function transferToPayer(lamports: number) {
+		return compileActions([
+			anchor.web3.SystemProgram.transfer({
+				fromPubkey: vaultSigner,
+				toPubkey: payerPublicKey,
+				lamports,
+			}),
+		]);
+	}
+
 	describe("flow: createVault() ->  executeProposalDirect()", () => {
 		it("works as expected", async () => {
 			const createVaultSignature = await program.methods
@@ -49,15 +72,10 @@ describe("Create vault, deposit, and execute proposal direct", () => {
 				lamports: withdrawAmount,
 			});
 
+			const compiled = compileActions([ix]);
 			const params = {
-				name: "Proposal 0001",
-				actions: [
-					{
-						accountSpecs: ix.keys,
-						data: ix.data,
-						programId: ix.programId,
-					},
-				],
+				nonce: new anchor.BN(0),
+				actions: compiled.actions,
 			};
 
 			const vaultBalanceA = await provider.connection.getBalance(vaultSigner);
@@ -71,17 +89,7 @@ describe("Create vault, deposit, and execute proposal direct", () => {
 					vault: vaultPublicKey,
 					proposer: payerPublicKey,
 				})
-				.remainingAccounts([
-					...ix.keys.map((key) => ({
-						...key,
-						isSigner: key.pubkey.equals(vaultSigner) ? false : key.isSigner,
-					})),
-					{
-						pubkey: ix.programId,
-						isSigner: false,
-						isWritable: false,
-					},
-				])
+				.remainingAccounts(toRemainingAccounts(compiled.allAccountKeys, compiled.actions))
 				.signers([payer])
 				.rpc();
 
@@ -97,6 +105,139 @@ describe("Create vault, deposit, and execute proposal direct", () => {
 				vaultBalanceA - vaultBalanceB === 1 * anchor.web3.LAMPORTS_PER_SOL,
 				"Vault balance after withdraw does not match",
 			);
+
+			const recordAccount = await program.account.directExecutionRecord.fetch(
+				findDirectExecutionRecord(0),
+			);
+			assert(recordAccount.caller.equals(payerPublicKey), "Record must name the caller");
+			assert.equal(recordAccount.nonce.toNumber(), 0);
+
+			const vaultAccount = await program.account.vault.fetch(vaultPublicKey);
+			assert.equal(vaultAccount.directExecutionNonce.toNumber(), 1);
+		});
+
+		it("rejects actions that pass the vault as writable", async () => {
+			const ix = anchor.web3.SystemProgram.transfer({
+				fromPubkey: vaultSigner,
+				toPubkey: vaultPublicKey,
+				lamports: 1,
+			});
+
+			const compiled = compileActions([ix]);
+
+			try {
+				await program.methods
+					.executeProposalDirect({ nonce: new anchor.BN(1), actions: compiled.actions })
+					.accounts({
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.remainingAccounts(toRemainingAccounts(compiled.allAccountKeys, compiled.actions))
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Writable vault must be rejected");
+			} catch (error) {
+				assert(
+					error instanceof anchor.AnchorError &&
+						error.error.errorCode.code === "WritableVaultInAction",
+					`Unexpected error: ${error}`,
+				);
+				assert(
+					error.logs.some((log) => log.includes("Action 0 targeting")),
+					"Failure log must name the action index",
+				);
+			}
+		});
+
+		it("logs the index of an action whose program fails", async () => {
+			const ix = anchor.web3.SystemProgram.transfer({
+				fromPubkey: vaultSigner,
+				toPubkey: payerPublicKey,
+				lamports: 1000 * anchor.web3.LAMPORTS_PER_SOL,
+			});
+			const compiled = compileActions([ix, ix]);
+
+			try {
+				await program.methods
+					.executeProposalDirect({ nonce: new anchor.BN(1), actions: compiled.actions })
+					.accounts({
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.remainingAccounts(toRemainingAccounts(compiled.allAccountKeys, compiled.actions))
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Overdrawing transfer must fail");
+			} catch (error) {
+				const logs: string[] = error.logs ?? [];
+				const lastInvoked = logs.filter((log) => log.includes("Invoking action")).pop();
+				assert(
+					lastInvoked?.includes(`Invoking action 0 on ${anchor.web3.SystemProgram.programId}`),
+					`Failing action must be logged before it is invoked: ${logs}`,
+				);
+			}
+		});
+
+		it("rejects nonces other than the vault's next nonce", async () => {
+			const compiled = transferToPayer(1000);
+
+			for (const nonce of [0, 2]) {
+				let rejected = false;
+				try {
+					await program.methods
+						.executeProposalDirect({ nonce: new anchor.BN(nonce), actions: compiled.actions })
+						.accounts({
+							vault: vaultPublicKey,
+							proposer: payerPublicKey,
+						})
+						.remainingAccounts(toRemainingAccounts(compiled.allAccountKeys, compiled.actions))
+						.signers([payer])
+						.rpc();
+				} catch (error) {
+					// A replayed nonce already has its record, so only a skipped nonce reaches
+					// the vault's nonce check.
+					rejected =
+						nonce === 0 ||
+						(error instanceof anchor.AnchorError &&
+							error.error.errorCode.code === "InvalidDirectExecutionNonce");
+				}
+				assert(rejected, `Nonce ${nonce} must be rejected`);
+			}
+		});
+
+		it("stops direct execution once disabled", async () => {
+			await program.methods
+				.disableDirectExecution()
+				.accounts({
+					vault: vaultPublicKey,
+					owner: payerPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+
+			const compiled = transferToPayer(1000);
+
+			try {
+				await program.methods
+					.executeProposalDirect({ nonce: new anchor.BN(1), actions: compiled.actions })
+					.accounts({
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.remainingAccounts(toRemainingAccounts(compiled.allAccountKeys, compiled.actions))
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Disabled direct execution must be rejected");
+			} catch (error) {
+				assert(
+					error instanceof anchor.AnchorError &&
+						error.error.errorCode.code === "DirectExecutionDisabled",
+					`Unexpected error: ${error}`,
+				);
+			}
 		});
 	});
 });
@@ -0,0 +1,172 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	assertErrorCode,
+	compileActions,
+	createVault,
+	findProposalActions,
+	newVaultFixture,
+	toChunkRemainingAccounts,
+} from './shared';
+
+describe("Executing several proposals at once", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const EXECUTION_RESULT_PREFIX = anchor.utils.bytes.utf8.encode("aacs_proposal_exec_results");
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+	const vaultFunding = anchor.web3.LAMPORTS_PER_SOL;
+
+	/** Creates a proposal transferring `lamports` from the vault to `recipient`. */
+	async function createTransferProposal(
+		name: string,
+		recipient: anchor.web3.PublicKey,
+		lamports: number,
+	) {
+		const proposal = anchor.web3.Keypair.generate();
+		const compiled = compileActions([
+			anchor.web3.SystemProgram.transfer({
+				fromPubkey: vaultSigner,
+				toPubkey: recipient,
+				lamports,
+			}),
+		]);
+
+		await program.methods
+			.createProposal({
+				name,
+				accountKeys: compiled.accountKeys,
+				actions: compiled.actions,
+				ephemeralSignerCount: 0,
+				coSigners: [],
+			})
+			.accountsPartial({
+				proposal: proposal.publicKey,
+				proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+				vault: vaultPublicKey,
+				proposer: payerPublicKey,
+			})
+			.signers([payer, proposal])
+			.rpc();
+
+		// Each proposal leads its accounts, followed by its execution result and its chunks.
+		const [executionResult] = anchor.web3.PublicKey.findProgramAddressSync(
+			[EXECUTION_RESULT_PREFIX, proposal.publicKey.toBuffer()],
+			program.programId,
+		);
+
+		return {
+			proposal: proposal.publicKey,
+			remainingAccounts: [
+				{ pubkey: proposal.publicKey, isSigner: false, isWritable: true },
+				{ pubkey: executionResult, isSigner: false, isWritable: true },
+				...toChunkRemainingAccounts(program.programId, proposal.publicKey, [compiled]),
+			],
+		};
+	}
+
+	async function executeProposals(batch: { remainingAccounts: anchor.web3.AccountMeta[] }[]) {
+		return program.methods
+			.executeProposals({ proposalCount: batch.length })
+			.accounts({
+				vault: vaultPublicKey,
+				caller: payerPublicKey,
+			})
+			.remainingAccounts(batch.flatMap(({ remainingAccounts }) => remainingAccounts))
+			.signers([payer])
+			.rpc();
+	}
+
+	before(async () => {
+		await createVault(program, payer, vaultFixture, vaultFunding);
+	});
+
+	describe("flow: createProposal() x2 -> executeProposals()", () => {
+		it("executes every proposal of the batch", async () => {
+			const contractor = anchor.web3.Keypair.generate().publicKey;
+			const accounting = anchor.web3.Keypair.generate().publicKey;
+			const lamports = anchor.web3.LAMPORTS_PER_SOL / 10;
+
+			const payout = await createTransferProposal("Payout", contractor, lamports);
+			const booking = await createTransferProposal("Accounting transfer", accounting, lamports);
+
+			const executionSignature = await executeProposals([payout, booking]);
+
+			console.log("Execution signature:", executionSignature);
+
+			for (const { proposal } of [payout, booking]) {
+				const proposalAccount = await program.account.proposal.fetch(proposal);
+				assert(proposalAccount.isExecuted, "Proposal must be executed");
+				assert.deepEqual(proposalAccount.proposalStage, { completed: {} });
+			}
+
+			assert.equal(await provider.connection.getBalance(contractor), lamports);
+			assert.equal(await provider.connection.getBalance(accounting), lamports);
+		});
+
+		it("executes none of the proposals when one fails", async () => {
+			const recipient = anchor.web3.Keypair.generate().publicKey;
+
+			const payout = await createTransferProposal(
+				"Affordable payout",
+				recipient,
+				anchor.web3.LAMPORTS_PER_SOL / 10,
+			);
+			const overdraft = await createTransferProposal("Overdraft", recipient, 2 * vaultFunding);
+
+			// The system program aborts the transaction, so there is no program error to match.
+			let reverted = false;
+			try {
+				await executeProposals([payout, overdraft]);
+			} catch {
+				reverted = true;
+			}
+			assert(reverted, "Failing proposal must revert the whole batch");
+
+			const proposalAccount = await program.account.proposal.fetch(payout.proposal);
+			assert(!proposalAccount.isExecuted, "No proposal of a failed batch may be executed");
+			assert.equal(await provider.connection.getBalance(recipient), 0);
+		});
+
+		it("caps what the proposals of a batch move together", async () => {
+			const lamports = anchor.web3.LAMPORTS_PER_SOL / 10;
+
+			await program.methods
+				.setVaultOutflowCap({ maxLamports: new anchor.BN(lamports * 1.5), tokenCaps: [] })
+				.accounts({
+					vault: vaultPublicKey,
+					owner: payerPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+
+			// Each proposal stays within the cap on its own.
+			const first = await createTransferProposal(
+				"First capped payout",
+				anchor.web3.Keypair.generate().publicKey,
+				lamports,
+			);
+			const second = await createTransferProposal(
+				"Second capped payout",
+				anchor.web3.Keypair.generate().publicKey,
+				lamports,
+			);
+
+			try {
+				await executeProposals([first, second]);
+				assert.fail("Batch moving more than the vault cap must revert");
+			} catch (error) {
+				assertErrorCode(error, "OutflowCapExceeded");
+			}
+		});
+	});
+});
@@ -0,0 +1,216 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import {
+	createTransferInstruction,
+	getOrCreateAssociatedTokenAccount,
+	mintTo,
+} from '@solana/spl-token';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	assertErrorCode,
+	compileActions,
+	createNewMint,
+	createVault,
+	ExecutionPolicy,
+	findProposalActions,
+	newVaultFixture,
+	toChunkRemainingAccounts,
+} from './shared';
+
+describe("Execution policies", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+
+	before(async () => {
+		await createVault(program, payer, vaultFixture, 2 * anchor.web3.LAMPORTS_PER_SOL);
+	});
+
+	/** Vault signer token account and a payer token account of a fresh mint. */
+	let vaultSignerAta: anchor.web3.PublicKey;
+	let payerAta: anchor.web3.PublicKey;
+
+	before(async () => {
+		const mint = await createNewMint(
+			provider.connection,
+			payer,
+			anchor.web3.Keypair.generate(),
+			6,
+		);
+		vaultSignerAta = (
+			await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, vaultSigner, true)
+		).address;
+		payerAta = (
+			await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, payerPublicKey)
+		).address;
+		await mintTo(provider.connection, payer, mint, vaultSignerAta, payer, 1_000_000);
+	});
+
+	/** Enough for a fresh recipient account to be rent exempt. */
+	const payoutLamports = anchor.web3.LAMPORTS_PER_SOL / 100;
+
+	function solTransfer(recipient: anchor.web3.PublicKey) {
+		return anchor.web3.SystemProgram.transfer({
+			fromPubkey: vaultSigner,
+			toPubkey: recipient,
+			lamports: payoutLamports,
+		});
+	}
+
+	function tokenTransfer(recipientAta: anchor.web3.PublicKey) {
+		return createTransferInstruction(vaultSignerAta, recipientAta, vaultSigner, 1000);
+	}
+
+	async function createAndExecute(
+		name: string,
+		ixs: anchor.web3.TransactionInstruction[],
+		policies: ExecutionPolicy[],
+	) {
+		const proposal = anchor.web3.Keypair.generate();
+		const compiled = compileActions(ixs, [], policies);
+
+		await program.methods
+			.createProposal({
+				name,
+				accountKeys: compiled.accountKeys,
+				actions: compiled.actions,
+				ephemeralSignerCount: 0,
+				coSigners: [],
+			})
+			.accountsPartial({
+				proposal: proposal.publicKey,
+				proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+				vault: vaultPublicKey,
+				proposer: payerPublicKey,
+			})
+			.signers([payer, proposal])
+			.rpc();
+
+		await program.methods
+			.executeProposal()
+			.accounts({
+				proposal: proposal.publicKey,
+				caller: payerPublicKey,
+			})
+			.remainingAccounts(toChunkRemainingAccounts(program.programId, proposal.publicKey, [compiled]))
+			.signers([payer])
+			.rpc();
+
+		return program.account.proposal.fetch(proposal.publicKey);
+	}
+
+	describe("flow: createProposal() with policies -> executeProposal()", () => {
+		it("pays a continue-on-error transfer to a fresh wallet", async () => {
+			const freshRecipient = anchor.web3.Keypair.generate().publicKey;
+
+			const proposalAccount = await createAndExecute(
+				"Fresh wallet payout",
+				[solTransfer(freshRecipient)],
+				[{ continueOnError: {} }],
+			);
+
+			assert.deepEqual(proposalAccount.actionOutcomes, [{ executed: {} }]);
+			assert.equal(await provider.connection.getBalance(freshRecipient), payoutLamports);
+		});
+
+		it("skips a continue-on-error token transfer to a missing token account", async () => {
+			// Not a token account, as a closed recipient token account would be.
+			const closedRecipientAta = anchor.web3.Keypair.generate().publicKey;
+
+			const proposalAccount = await createAndExecute(
+				"Bulk payout",
+				[tokenTransfer(payerAta), tokenTransfer(closedRecipientAta)],
+				[{ continueOnError: {} }, { continueOnError: {} }],
+			);
+
+			assert(proposalAccount.isExecuted, "Proposal must be executed");
+			assert.deepEqual(proposalAccount.actionOutcomes, [{ executed: {} }, { skipped: {} }]);
+		});
+
+		it("skips a whole group when one member fails its prechecks", async () => {
+			const closedRecipientAta = anchor.web3.Keypair.generate().publicKey;
+
+			const proposalAccount = await createAndExecute(
+				"Grouped payout",
+				[tokenTransfer(payerAta), tokenTransfer(closedRecipientAta), solTransfer(payerPublicKey)],
+				[{ group: { id: 1 } }, { group: { id: 1 } }, { atomic: {} }],
+			);
+
+			assert.deepEqual(proposalAccount.actionOutcomes, [
+				{ skipped: {} },
+				{ skipped: {} },
+				{ executed: {} },
+			]);
+		});
+
+		it("rejects a group continuing into an appended chunk", async () => {
+			const proposal = anchor.web3.Keypair.generate();
+			const chunk0 = compileActions([solTransfer(payerPublicKey)], [], [{ group: { id: 2 } }]);
+			const chunk1 = compileActions([solTransfer(payerPublicKey)], [], [{ group: { id: 2 } }]);
+
+			await program.methods
+				.createProposal({
+					name: "Group across chunks",
+					accountKeys: chunk0.accountKeys,
+					actions: chunk0.actions,
+					ephemeralSignerCount: 0,
+					coSigners: [],
+				})
+				.accountsPartial({
+					proposal: proposal.publicKey,
+					proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.signers([payer, proposal])
+				.rpc();
+
+			try {
+				await program.methods
+					.appendActions({
+						accountKeys: chunk1.accountKeys,
+						actions: chunk1.actions,
+					})
+					.accountsPartial({
+						proposal: proposal.publicKey,
+						proposalActions: findProposalActions(program.programId, proposal.publicKey, 1),
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Group split across chunks must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "ActionGroupSpansChunks");
+			}
+		});
+
+		it("rejects groups whose members are not consecutive", async () => {
+			try {
+				await createAndExecute(
+					"Split group",
+					[solTransfer(payerPublicKey), solTransfer(payerPublicKey), solTransfer(payerPublicKey)],
+					[{ group: { id: 1 } }, { atomic: {} }, { group: { id: 1 } }],
+				);
+
+				assert.fail("Non-consecutive group must be rejected");
+			} catch (error) {
+				assert(
+					error instanceof anchor.AnchorError &&
+						error.error.errorCode.code === "NonContiguousActionGroup",
+					`Unexpected error: ${error}`,
+				);
+			}
+		});
+	});
+});
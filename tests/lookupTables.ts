@@ -0,0 +1,134 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	compileActions,
+	createProposalLookupTable,
+	createVault,
+	findProposalActions,
+	newVaultFixture,
+	sendV0Transaction,
+	toChunkRemainingAccounts,
+} from './shared';
+
+describe("Executing proposals through address lookup tables", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const RECIPIENT_COUNT = 40;
+	const CHUNK_LEN = 10;
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+	const recipients = [...Array(RECIPIENT_COUNT)].map(
+		() => anchor.web3.Keypair.generate().publicKey,
+	);
+
+	before(async () => {
+		await createVault(program, payer, vaultFixture, 2 * anchor.web3.LAMPORTS_PER_SOL);
+	});
+
+	describe("flow: createProposal() -> appendActions() -> executeProposal() in a v0 transaction", () => {
+		it("executes a payroll proposal touching more accounts than a legacy transaction holds", async () => {
+			const lamports = anchor.web3.LAMPORTS_PER_SOL / 100;
+			const proposal = anchor.web3.Keypair.generate();
+
+			const chunks = [];
+			for (let start = 0; start < RECIPIENT_COUNT; start += CHUNK_LEN) {
+				chunks.push(
+					compileActions(
+						recipients.slice(start, start + CHUNK_LEN).map((toPubkey) =>
+							anchor.web3.SystemProgram.transfer({
+								fromPubkey: vaultSigner,
+								toPubkey,
+								lamports,
+							}),
+						),
+					),
+				);
+			}
+
+			await program.methods
+				.createProposal({
+					name: "Payroll",
+					accountKeys: chunks[0].accountKeys,
+					actions: chunks[0].actions,
+					ephemeralSignerCount: 0,
+					coSigners: [],
+				})
+				.accountsPartial({
+					proposal: proposal.publicKey,
+					proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.signers([payer, proposal])
+				.rpc();
+
+			for (let chunkIndex = 1; chunkIndex < chunks.length; chunkIndex++) {
+				await program.methods
+					.appendActions({
+						accountKeys: chunks[chunkIndex].accountKeys,
+						actions: chunks[chunkIndex].actions,
+					})
+					.accountsPartial({
+						proposal: proposal.publicKey,
+						proposalActions: findProposalActions(
+							program.programId,
+							proposal.publicKey,
+							chunkIndex,
+						),
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.signers([payer])
+					.rpc();
+			}
+
+			const lookupTable = await createProposalLookupTable(
+				provider.connection,
+				payer,
+				program.programId,
+				proposal.publicKey,
+				chunks,
+			);
+
+			const executeIx = await program.methods
+				.executeProposal()
+				.accounts({
+					proposal: proposal.publicKey,
+					caller: payerPublicKey,
+				})
+				.remainingAccounts(
+					toChunkRemainingAccounts(program.programId, proposal.publicKey, chunks),
+				)
+				.instruction();
+
+			const executionSignature = await sendV0Transaction(
+				provider.connection,
+				payer,
+				[
+					anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 }),
+					executeIx,
+				],
+				[lookupTable],
+			);
+
+			console.log("Execution signature:", executionSignature);
+
+			const proposalAccount = await program.account.proposal.fetch(proposal.publicKey);
+			assert(proposalAccount.isExecuted, "Proposal must be executed");
+			assert.equal(proposalAccount.actionOutcomes.length, RECIPIENT_COUNT);
+
+			for (const recipient of recipients) {
+				assert.equal(await provider.connection.getBalance(recipient), lamports);
+			}
+		});
+	});
+});
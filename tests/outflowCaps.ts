@@ -0,0 +1,239 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import {
+	createApproveInstruction,
+	getOrCreateAssociatedTokenAccount,
+	mintTo,
+} from '@solana/spl-token';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	assertErrorCode,
+	calculateProposalSize,
+	compileActions,
+	createNewMint,
+	createVault,
+	findProposalActions,
+	newVaultFixture,
+	OutflowCap,
+	toChunkRemainingAccounts,
+	toRemainingAccounts,
+} from './shared';
+
+describe("Outflow caps", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+	const vaultCap = anchor.web3.LAMPORTS_PER_SOL / 2;
+
+	/** Creates a proposal transferring `lamports` out of the vault and executes it. */
+	async function createAndExecute(name: string, lamports: number, outflowCap?: OutflowCap) {
+		const proposal = anchor.web3.Keypair.generate();
+		const compiled = compileActions([
+			anchor.web3.SystemProgram.transfer({
+				fromPubkey: vaultSigner,
+				toPubkey: payerPublicKey,
+				lamports,
+			}),
+		]);
+
+		await program.methods
+			.createProposal({
+				name,
+				accountKeys: compiled.accountKeys,
+				actions: compiled.actions,
+				ephemeralSignerCount: 0,
+				coSigners: [],
+			})
+			.accountsPartial({
+				proposal: proposal.publicKey,
+				proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+				vault: vaultPublicKey,
+				proposer: payerPublicKey,
+			})
+			.signers([payer, proposal])
+			.rpc();
+
+		if (outflowCap) {
+			await program.methods
+				.setProposalOutflowCap(outflowCap)
+				.accounts({
+					proposal: proposal.publicKey,
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+
+			const proposalInfo = await provider.connection.getAccountInfo(proposal.publicKey);
+			assert.equal(proposalInfo!.data.length, 8 + calculateProposalSize(name, 0, 0, 0, outflowCap));
+		}
+
+		await program.methods
+			.executeProposal()
+			.accounts({
+				proposal: proposal.publicKey,
+				caller: payerPublicKey,
+			})
+			.remainingAccounts(
+				toChunkRemainingAccounts(program.programId, proposal.publicKey, [compiled]),
+			)
+			.signers([payer])
+			.rpc();
+
+		return proposal.publicKey;
+	}
+
+	/** Executes `instructions` directly with the vault's next direct execution nonce. */
+	async function executeDirect(instructions: anchor.web3.TransactionInstruction[]) {
+		const compiled = compileActions(instructions);
+		const vaultAccount = await program.account.vault.fetch(vaultPublicKey);
+
+		return program.methods
+			.executeProposalDirect({ nonce: vaultAccount.directExecutionNonce, actions: compiled.actions })
+			.accounts({
+				vault: vaultPublicKey,
+				proposer: payerPublicKey,
+			})
+			.remainingAccounts(toRemainingAccounts(compiled.allAccountKeys, compiled.actions))
+			.signers([payer])
+			.rpc();
+	}
+
+	before(async () => {
+		await createVault(program, payer, vaultFixture, 2 * anchor.web3.LAMPORTS_PER_SOL);
+
+		await program.methods
+			.setVaultOutflowCap({ maxLamports: new anchor.BN(vaultCap), tokenCaps: [] })
+			.accounts({
+				vault: vaultPublicKey,
+				owner: payerPublicKey,
+			})
+			.signers([payer])
+			.rpc();
+	});
+
+	describe("flow: setVaultOutflowCap() -> createProposal() -> setProposalOutflowCap() -> executeProposal()", () => {
+		it("executes a proposal within the vault cap", async () => {
+			const proposal = await createAndExecute("Within vault cap", vaultCap);
+
+			const proposalAccount = await program.account.proposal.fetch(proposal);
+			assert(proposalAccount.isExecuted, "Proposal must be executed");
+		});
+
+		it("reverts a proposal moving more than the vault cap", async () => {
+			try {
+				await createAndExecute("Over vault cap", vaultCap + 1);
+				assert.fail("Outflow over the vault cap must revert the execution");
+			} catch (error) {
+				assertErrorCode(error, "OutflowCapExceeded");
+			}
+		});
+
+		it("reverts a proposal moving more than its own cap", async () => {
+			try {
+				await createAndExecute("Over proposal cap", vaultCap / 2, {
+					maxLamports: new anchor.BN(vaultCap / 4),
+					tokenCaps: [],
+				});
+				assert.fail("Outflow over the proposal cap must revert the execution");
+			} catch (error) {
+				assertErrorCode(error, "OutflowCapExceeded");
+			}
+		});
+
+		it("rejects too many token caps", async () => {
+			const tokenCaps = [...Array(9)].map(() => ({
+				mint: anchor.web3.Keypair.generate().publicKey,
+				maxAmount: new anchor.BN(1),
+			}));
+
+			try {
+				await program.methods
+					.setVaultOutflowCap({ maxLamports: null, tokenCaps })
+					.accounts({
+						vault: vaultPublicKey,
+						owner: payerPublicKey,
+					})
+					.signers([payer])
+					.rpc();
+				assert.fail("Too many token caps must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "TooManyTokenOutflowCaps");
+			}
+		});
+	});
+
+	describe("flow: setVaultOutflowCap() -> executeProposalDirect()", () => {
+		it("reverts a direct execution moving more than the vault cap", async () => {
+			try {
+				await executeDirect([
+					anchor.web3.SystemProgram.transfer({
+						fromPubkey: vaultSigner,
+						toPubkey: payerPublicKey,
+						lamports: vaultCap + 1,
+					}),
+				]);
+				assert.fail("Outflow over the vault cap must revert the direct execution");
+			} catch (error) {
+				assertErrorCode(error, "OutflowCapExceeded");
+			}
+		});
+
+		it("counts assigning the vault signer to another program as moving all its lamports", async () => {
+			try {
+				// Moves nothing now, but hands the whole balance to the new owner program.
+				await executeDirect([
+					anchor.web3.SystemProgram.assign({
+						accountPubkey: vaultSigner,
+						programId: anchor.web3.Keypair.generate().publicKey,
+					}),
+				]);
+				assert.fail("Assigning the vault signer away must count against the lamport cap");
+			} catch (error) {
+				assertErrorCode(error, "OutflowCapExceeded");
+			}
+		});
+
+		it("counts approving a delegate as moving the whole token balance", async () => {
+			const mint = await createNewMint(
+				provider.connection,
+				payer,
+				anchor.web3.Keypair.generate(),
+				6,
+			);
+			const vaultSignerAta = (
+				await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, vaultSigner, true)
+			).address;
+			await mintTo(provider.connection, payer, mint, vaultSignerAta, payer, 1_000_000);
+
+			await program.methods
+				.setVaultOutflowCap({
+					maxLamports: new anchor.BN(vaultCap),
+					tokenCaps: [{ mint, maxAmount: new anchor.BN(100_000) }],
+				})
+				.accounts({
+					vault: vaultPublicKey,
+					owner: payerPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+
+			try {
+				// Moves nothing now, but lets the delegate take the balance afterwards.
+				await executeDirect([createApproveInstruction(vaultSignerAta, payerPublicKey, vaultSigner, 1)]);
+				assert.fail("Approving a delegate must count against the token cap");
+			} catch (error) {
+				assertErrorCode(error, "OutflowCapExceeded");
+			}
+		});
+	});
+});
@@ -0,0 +1,242 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import {
+	TOKEN_PROGRAM_ID,
+	createTransferInstruction,
+	getOrCreateAssociatedTokenAccount,
+	mintTo,
+} from '@solana/spl-token';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	assertErrorCode,
+	CompiledActions,
+	compileActions,
+	createNewMint,
+	createVault,
+	findProposalActions,
+	newVaultFixture,
+	toChunkRemainingAccounts,
+} from './shared';
+
+describe("Action placeholders", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const PROGRAM_ALLOWLIST_PREFIX = anchor.utils.bytes.utf8.encode("aacs_program_allowlist");
+	/** Offset of the lamports in system program transfer data, after the instruction tag. */
+	const SYSTEM_TRANSFER_AMOUNT_OFFSET = 4;
+	/** Offset of the amount in token program transfer data, after the instruction tag. */
+	const TOKEN_TRANSFER_AMOUNT_OFFSET = 1;
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+	const [programAllowlist] = anchor.web3.PublicKey.findProgramAddressSync(
+		[PROGRAM_ALLOWLIST_PREFIX, vaultPublicKey.toBuffer()],
+		program.programId,
+	);
+
+	async function createProposal(name: string, compiled: CompiledActions) {
+		const proposal = anchor.web3.Keypair.generate();
+
+		await program.methods
+			.createProposal({
+				name,
+				accountKeys: compiled.accountKeys,
+				actions: compiled.actions,
+				ephemeralSignerCount: 0,
+				coSigners: [],
+			})
+			.accountsPartial({
+				proposal: proposal.publicKey,
+				proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+				vault: vaultPublicKey,
+				proposer: payerPublicKey,
+			})
+			.signers([payer, proposal])
+			.rpc();
+
+		return proposal.publicKey;
+	}
+
+	async function executeProposal(proposal: anchor.web3.PublicKey, compiled: CompiledActions) {
+		return program.methods
+			.executeProposal()
+			.accounts({
+				proposal,
+				caller: payerPublicKey,
+			})
+			.remainingAccounts(toChunkRemainingAccounts(program.programId, proposal, [compiled]))
+			.signers([payer])
+			.rpc();
+	}
+
+	before(async () => {
+		await createVault(program, payer, vaultFixture, anchor.web3.LAMPORTS_PER_SOL);
+	});
+
+	describe("flow: createProposal() -> executeProposal() with placeholders", () => {
+		it("sweeps the vault signer lamports above a reserve", async () => {
+			const reserve = anchor.web3.LAMPORTS_PER_SOL / 10;
+			const compiled = compileActions([
+				anchor.web3.SystemProgram.transfer({
+					fromPubkey: vaultSigner,
+					toPubkey: payerPublicKey,
+					lamports: 0,
+				}),
+			]);
+			compiled.actions[0].placeholders = [
+				{
+					offset: SYSTEM_TRANSFER_AMOUNT_OFFSET,
+					source: { vaultLamports: { reserve: new anchor.BN(reserve) } },
+					basisPoints: 10_000,
+				},
+			];
+
+			const proposal = await createProposal("Sweep lamports", compiled);
+			const executionSignature = await executeProposal(proposal, compiled);
+
+			console.log("Execution signature:", executionSignature);
+
+			assert.equal(await provider.connection.getBalance(vaultSigner), reserve);
+		});
+
+		it("pays a share of a token balance", async () => {
+			const mint = await createNewMint(
+				provider.connection,
+				payer,
+				anchor.web3.Keypair.generate(),
+				6,
+			);
+			const payerAta = (
+				await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, payerPublicKey)
+			).address;
+			const vaultSignerAta = (
+				await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, vaultSigner, true)
+			).address;
+			await mintTo(provider.connection, payer, mint, vaultSignerAta, payer, 1_000_000);
+
+			const compiled = compileActions([
+				createTransferInstruction(vaultSignerAta, payerAta, vaultSigner, 0),
+			]);
+			compiled.actions[0].placeholders = [
+				{
+					offset: TOKEN_TRANSFER_AMOUNT_OFFSET,
+					source: {
+						tokenBalance: {
+							accountIndex: compiled.allAccountKeys.findIndex((key) => key.equals(vaultSignerAta)),
+						},
+					},
+					basisPoints: 1_000,
+				},
+			];
+
+			const proposal = await createProposal("Pay 10% of tokens", compiled);
+			await executeProposal(proposal, compiled);
+
+			const vaultBalance = await provider.connection.getTokenAccountBalance(vaultSignerAta);
+			assert.equal(vaultBalance.value.amount, "900000");
+		});
+
+		it("rejects placeholders outside the instruction data", async () => {
+			const compiled = compileActions([
+				anchor.web3.SystemProgram.transfer({
+					fromPubkey: vaultSigner,
+					toPubkey: payerPublicKey,
+					lamports: 0,
+				}),
+			]);
+			compiled.actions[0].placeholders = [
+				{
+					offset: SYSTEM_TRANSFER_AMOUNT_OFFSET + 1,
+					source: { vaultLamports: { reserve: new anchor.BN(0) } },
+					basisPoints: 10_000,
+				},
+			];
+
+			try {
+				await createProposal("Misplaced placeholder", compiled);
+				assert.fail("Placeholder past the end of the data must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "InvalidPlaceholder");
+			}
+		});
+
+		it("rejects placeholders over an allowlisted instruction discriminator", async () => {
+			const mint = await createNewMint(
+				provider.connection,
+				payer,
+				anchor.web3.Keypair.generate(),
+				6,
+			);
+			const payerAta = (
+				await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, payerPublicKey)
+			).address;
+			const vaultSignerAta = (
+				await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, vaultSigner, true)
+			).address;
+
+			await program.methods
+				.setProgramAllowlist({
+					enabled: true,
+					programs: [
+						// TokenInstruction::Transfer
+						{ programId: TOKEN_PROGRAM_ID, discriminators: [Buffer.from([3])] },
+					],
+				})
+				.accountsPartial({
+					owner: payerPublicKey,
+					vault: vaultPublicKey,
+					programAllowlist,
+				})
+				.signers([payer])
+				.rpc();
+
+			// A balance written at offset 0 would replace the allowed Transfer tag.
+			const compiled = compileActions([
+				createTransferInstruction(vaultSignerAta, payerAta, vaultSigner, 0),
+			]);
+			compiled.actions[0].placeholders = [
+				{
+					offset: 0,
+					source: {
+						tokenBalance: {
+							accountIndex: compiled.allAccountKeys.findIndex((key) => key.equals(vaultSignerAta)),
+						},
+					},
+					basisPoints: 10_000,
+				},
+			];
+
+			const proposal = anchor.web3.Keypair.generate();
+
+			try {
+				await program.methods
+					.createProposal({
+						name: "Discriminator placeholder",
+						accountKeys: compiled.accountKeys,
+						actions: compiled.actions,
+						ephemeralSignerCount: 0,
+						coSigners: [],
+					})
+					.accountsPartial({
+						proposal: proposal.publicKey,
+						proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+						programAllowlist,
+					})
+					.signers([payer, proposal])
+					.rpc();
+				assert.fail("Placeholder over the allowlisted discriminator must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "InvalidPlaceholder");
+			}
+		});
+	});
+});
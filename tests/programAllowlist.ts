@@ -0,0 +1,143 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import { compileActions, sleep, toRemainingAccounts } from './shared';
+
+describe("Program allowlist flow", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const VAULT_SIGNER_PREFIX = anchor.utils.bytes.utf8.encode("aacs_vault_signer");
+	const PROGRAM_ALLOWLIST_PREFIX = anchor.utils.bytes.utf8.encode("aacs_program_allowlist");
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vault = anchor.web3.Keypair.generate();
+	const vaultPublicKey = vault.publicKey;
+	const [vaultSigner, vaultBump] = anchor.web3.PublicKey.findProgramAddressSync(
+		[VAULT_SIGNER_PREFIX, vaultPublicKey.toBuffer()],
+		program.programId,
+	);
+	const [programAllowlist] = anchor.web3.PublicKey.findProgramAddressSync(
+		[PROGRAM_ALLOWLIST_PREFIX, vaultPublicKey.toBuffer()],
+		program.programId,
+	);
+
+	describe("flow: createVault() -> setProgramAllowlist() -> executeProposalDirect()", () => {
+		it("works as expected", async () => {
+			const createVaultSignature = await program.methods
+				.createVault({ owner: payerPublicKey, signerBump: vaultBump })
+				.accounts({
+					vault: vaultPublicKey,
+				})
+				.postInstructions([
+					anchor.web3.SystemProgram.transfer({
+						fromPubkey: payerPublicKey,
+						toPubkey: vaultSigner,
+						lamports: 10 * anchor.web3.LAMPORTS_PER_SOL,
+					}),
+				])
+				.signers([vault, payer])
+				.rpc();
+
+			console.log("Create Vault Signature: ", createVaultSignature);
+
+			const setAllowlistSignature = await program.methods
+				.setProgramAllowlist({
+					enabled: true,
+					programs: [{ programId: TOKEN_PROGRAM_ID, discriminators: [] }],
+				})
+				.accountsPartial({
+					owner: payerPublicKey,
+					vault: vaultPublicKey,
+					programAllowlist,
+				})
+				.signers([payer])
+				.rpc();
+
+			console.log("Set Program Allowlist Signature: ", setAllowlistSignature);
+
+			const ix = anchor.web3.SystemProgram.transfer({
+				fromPubkey: vaultSigner,
+				toPubkey: payerPublicKey,
+				lamports: 1 * anchor.web3.LAMPORTS_PER_SOL,
+			});
+
+			const compiled = compileActions([ix]);
+			const params = {
+				nonce: new anchor.BN(0),
+				actions: compiled.actions,
+			};
+
+			const remainingAccounts = toRemainingAccounts(compiled.allAccountKeys, compiled.actions);
+
+			try {
+				await program.methods
+					.executeProposalDirect(params)
+					.accountsPartial({
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+						programAllowlist,
+					})
+					.remainingAccounts(remainingAccounts)
+					.signers([payer])
+					.rpc();
+
+				assert.fail("System program must be rejected by the allowlist");
+			} catch (error) {
+				assert(
+					error instanceof anchor.AnchorError &&
+						error.error.errorCode.code === "ProgramNotAllowed",
+					`Unexpected error: ${error}`,
+				);
+			}
+
+			await program.methods
+				.setProgramAllowlist({
+					enabled: true,
+					programs: [
+						{ programId: TOKEN_PROGRAM_ID, discriminators: [] },
+						// SystemInstruction::Transfer
+						{ programId: anchor.web3.SystemProgram.programId, discriminators: [Buffer.from([2, 0, 0, 0])] },
+					],
+				})
+				.accountsPartial({
+					owner: payerPublicKey,
+					vault: vaultPublicKey,
+					programAllowlist,
+				})
+				.signers([payer])
+				.rpc();
+
+			const vaultBalanceA = await provider.connection.getBalance(vaultSigner);
+			const executionSignature = await program.methods
+				.executeProposalDirect(params)
+				.accountsPartial({
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+					programAllowlist,
+				})
+				.remainingAccounts(remainingAccounts)
+				.signers([payer])
+				.rpc();
+
+			console.log("Execution signature:", executionSignature);
+
+			await sleep(1000);
+			const vaultBalanceB = await provider.connection.getBalance(vaultSigner);
+			assert(
+				vaultBalanceA - vaultBalanceB === 1 * anchor.web3.LAMPORTS_PER_SOL,
+				"Vault balance after withdraw does not match",
+			);
+
+			const allowlistAccount = await program.account.programAllowlist.fetch(programAllowlist);
+			assert(allowlistAccount.programs.length === 2, "Allowlist must hold both programs");
+		});
+	});
+});
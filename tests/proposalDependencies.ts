@@ -0,0 +1,214 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import {
+	createTransferInstruction,
+	getOrCreateAssociatedTokenAccount,
+	mintTo,
+} from '@solana/spl-token';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	assertErrorCode,
+	calculateProposalSize,
+	CompiledActions,
+	compileActions,
+	createNewMint,
+	createVault,
+	findProposalActions,
+	newVaultFixture,
+	toChunkRemainingAccounts,
+} from './shared';
+
+describe("Proposal dependencies", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+	const lamports = anchor.web3.LAMPORTS_PER_SOL / 10;
+
+	/** Creates a proposal transferring `lamports` from the vault to `recipient`. */
+	async function createTransferProposal(name: string, recipient: anchor.web3.PublicKey) {
+		const compiled = compileActions([
+			anchor.web3.SystemProgram.transfer({
+				fromPubkey: vaultSigner,
+				toPubkey: recipient,
+				lamports,
+			}),
+		]);
+
+		return createProposal(name, compiled);
+	}
+
+	async function createProposal(name: string, compiled: CompiledActions) {
+		const proposal = anchor.web3.Keypair.generate();
+
+		await program.methods
+			.createProposal({
+				name,
+				accountKeys: compiled.accountKeys,
+				actions: compiled.actions,
+				ephemeralSignerCount: 0,
+				coSigners: [],
+			})
+			.accountsPartial({
+				proposal: proposal.publicKey,
+				proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+				vault: vaultPublicKey,
+				proposer: payerPublicKey,
+			})
+			.signers([payer, proposal])
+			.rpc();
+
+		return { proposal: proposal.publicKey, compiled };
+	}
+
+	async function executeProposal(
+		proposal: anchor.web3.PublicKey,
+		compiled: CompiledActions,
+		dependencies: anchor.web3.PublicKey[] = [],
+	) {
+		return program.methods
+			.executeProposal()
+			.accounts({
+				proposal,
+				caller: payerPublicKey,
+			})
+			.remainingAccounts([
+				...toChunkRemainingAccounts(program.programId, proposal, [compiled]),
+				...dependencies.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false })),
+			])
+			.signers([payer])
+			.rpc();
+	}
+
+	before(async () => {
+		await createVault(program, payer, vaultFixture, anchor.web3.LAMPORTS_PER_SOL);
+	});
+
+	describe("flow: createProposal() -> setProposalDependencies() -> executeProposal()", () => {
+		it("executes only after its dependency completed", async () => {
+			const recipient = anchor.web3.Keypair.generate().publicKey;
+			const first = await createTransferProposal("Fund account", recipient);
+			const second = await createTransferProposal("Top up account", recipient);
+
+			await program.methods
+				.setProposalDependencies({ dependencies: [first.proposal] })
+				.accounts({
+					proposal: second.proposal,
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.remainingAccounts([{ pubkey: first.proposal, isSigner: false, isWritable: false }])
+				.signers([payer])
+				.rpc();
+
+			const proposalInfo = await provider.connection.getAccountInfo(second.proposal);
+			assert.equal(
+				proposalInfo!.data.length,
+				8 + calculateProposalSize("Top up account", 0, 0, 0, null, 1),
+			);
+
+			try {
+				await executeProposal(second.proposal, second.compiled, [first.proposal]);
+				assert.fail("Proposal must not execute before its dependency");
+			} catch (error) {
+				assertErrorCode(error, "DependencyNotCompleted");
+			}
+
+			try {
+				await executeProposal(second.proposal, second.compiled);
+				assert.fail("Dependency account must be passed");
+			} catch (error) {
+				assertErrorCode(error, "MissingDependencyAccount");
+			}
+
+			await executeProposal(first.proposal, first.compiled);
+			await executeProposal(second.proposal, second.compiled, [first.proposal]);
+
+			const proposalAccount = await program.account.proposal.fetch(second.proposal);
+			assert(proposalAccount.isExecuted, "Proposal must be executed");
+			assert.equal(await provider.connection.getBalance(recipient), 2 * lamports);
+		});
+
+		it("does not execute after a dependency that skipped actions", async () => {
+			const mint = await createNewMint(
+				provider.connection,
+				payer,
+				anchor.web3.Keypair.generate(),
+				6,
+			);
+			const vaultSignerAta = (
+				await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, vaultSigner, true)
+			).address;
+			await mintTo(provider.connection, payer, mint, vaultSignerAta, payer, 1_000_000);
+
+			// Not a token account, so the transfer is skipped and the proposal completes partially.
+			const missingAta = anchor.web3.Keypair.generate().publicKey;
+			const first = await createProposal(
+				"Partial payout",
+				compileActions(
+					[createTransferInstruction(vaultSignerAta, missingAta, vaultSigner, 1000)],
+					[],
+					[{ continueOnError: {} }],
+				),
+			);
+			const second = await createTransferProposal(
+				"After partial payout",
+				anchor.web3.Keypair.generate().publicKey,
+			);
+
+			await program.methods
+				.setProposalDependencies({ dependencies: [first.proposal] })
+				.accounts({
+					proposal: second.proposal,
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.remainingAccounts([{ pubkey: first.proposal, isSigner: false, isWritable: false }])
+				.signers([payer])
+				.rpc();
+
+			await executeProposal(first.proposal, first.compiled);
+
+			const firstAccount = await program.account.proposal.fetch(first.proposal);
+			assert.deepEqual(firstAccount.actionOutcomes, [{ skipped: {} }]);
+
+			try {
+				await executeProposal(second.proposal, second.compiled, [first.proposal]);
+				assert.fail("Proposal must not execute after a partially executed dependency");
+			} catch (error) {
+				assertErrorCode(error, "DependencyNotCompleted");
+			}
+		});
+
+		it("rejects depending on itself", async () => {
+			const { proposal } = await createTransferProposal(
+				"Self dependent",
+				anchor.web3.Keypair.generate().publicKey,
+			);
+
+			try {
+				await program.methods
+					.setProposalDependencies({ dependencies: [proposal] })
+					.accounts({
+						proposal,
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.remainingAccounts([{ pubkey: proposal, isSigner: false, isWritable: false }])
+					.signers([payer])
+					.rpc();
+				assert.fail("Self dependency must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "InvalidDependency");
+			}
+		});
+	});
+});
@@ -0,0 +1,167 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	compileActions,
+	createLookupTable,
+	createVault,
+	findProposalActions,
+	newVaultFixture,
+	toChunkRemainingAccounts,
+} from './shared';
+
+describe("Create proposal from a transaction message", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+	const recipient = anchor.web3.Keypair.generate().publicKey;
+	const lamports = anchor.web3.LAMPORTS_PER_SOL / 10;
+
+	function transferIx() {
+		return anchor.web3.SystemProgram.transfer({
+			fromPubkey: vaultSigner,
+			toPubkey: recipient,
+			lamports,
+		});
+	}
+
+	/**
+	 * Creates a proposal from `message`, resolving lookups through `lookupTables`, and
+	 * executes it.
+	 */
+	async function createAndExecute(
+		name: string,
+		message: Uint8Array,
+		lookupTables: anchor.web3.PublicKey[] = [],
+	) {
+		const proposal = anchor.web3.Keypair.generate();
+
+		await program.methods
+			.createProposalFromMessage({
+				name,
+				message: Buffer.from(message),
+				subAccountIndices: [],
+				ephemeralSignerCount: 0,
+				coSigners: [],
+			})
+			.accountsPartial({
+				proposal: proposal.publicKey,
+				proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+				vault: vaultPublicKey,
+				proposer: payerPublicKey,
+			})
+			.remainingAccounts(
+				lookupTables.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false })),
+			)
+			.signers([payer, proposal])
+			.rpc();
+
+		// Decompiled key tables list each program before its accounts, like `compileActions()`.
+		const compiled = compileActions([transferIx()]);
+
+		await program.methods
+			.executeProposal()
+			.accounts({
+				proposal: proposal.publicKey,
+				caller: payerPublicKey,
+			})
+			.remainingAccounts(
+				toChunkRemainingAccounts(program.programId, proposal.publicKey, [compiled]),
+			)
+			.signers([payer])
+			.rpc();
+
+		return proposal.publicKey;
+	}
+
+	before(async () => {
+		await createVault(program, payer, vaultFixture, anchor.web3.LAMPORTS_PER_SOL);
+	});
+
+	describe("flow: createProposalFromMessage() -> executeProposal()", () => {
+		it("decompiles a legacy message", async () => {
+			const { blockhash } = await provider.connection.getLatestBlockhash();
+			const message = new anchor.web3.TransactionMessage({
+				payerKey: vaultSigner,
+				recentBlockhash: blockhash,
+				instructions: [transferIx()],
+			}).compileToLegacyMessage();
+
+			const proposal = await createAndExecute("Legacy message", message.serialize());
+
+			const proposalAccount = await program.account.proposal.fetch(proposal);
+			assert.equal(proposalAccount.actionCount, 1);
+			assert(proposalAccount.isExecuted, "Proposal must be executed");
+			assert.equal(await provider.connection.getBalance(recipient), lamports);
+		});
+
+		it("decompiles a v0 message", async () => {
+			const { blockhash } = await provider.connection.getLatestBlockhash();
+			const message = new anchor.web3.TransactionMessage({
+				payerKey: vaultSigner,
+				recentBlockhash: blockhash,
+				instructions: [transferIx()],
+			}).compileToV0Message();
+
+			await createAndExecute("V0 message", message.serialize());
+
+			assert.equal(await provider.connection.getBalance(recipient), 2 * lamports);
+		});
+
+		it("resolves accounts loaded from address lookup tables", async () => {
+			const lookupTable = await createLookupTable(provider.connection, payer, [recipient]);
+			const { blockhash } = await provider.connection.getLatestBlockhash();
+			const message = new anchor.web3.TransactionMessage({
+				payerKey: vaultSigner,
+				recentBlockhash: blockhash,
+				instructions: [transferIx()],
+			}).compileToV0Message([lookupTable]);
+
+			assert.equal(message.addressTableLookups.length, 1);
+
+			await createAndExecute("V0 message with lookups", message.serialize(), [lookupTable.key]);
+
+			assert.equal(await provider.connection.getBalance(recipient), 3 * lamports);
+		});
+
+		it("rejects malformed messages", async () => {
+			const proposal = anchor.web3.Keypair.generate();
+
+			try {
+				await program.methods
+					.createProposalFromMessage({
+						name: "Malformed message",
+						message: Buffer.from([1, 0, 0]),
+						subAccountIndices: [],
+						ephemeralSignerCount: 0,
+						coSigners: [],
+					})
+					.accountsPartial({
+						proposal: proposal.publicKey,
+						proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.signers([payer, proposal])
+					.rpc();
+
+				assert.fail("Malformed message must be rejected");
+			} catch (error) {
+				assert(
+					error instanceof anchor.AnchorError &&
+						error.error.errorCode.code === "InvalidTransactionMessage",
+					`Unexpected error: ${error}`,
+				);
+			}
+		});
+	});
+});
@@ -0,0 +1,161 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	assertErrorCode,
+	calculateProposalSize,
+	compileActions,
+	createVault,
+	findProposalActions,
+	Guard,
+	newVaultFixture,
+	toChunkRemainingAccounts,
+} from './shared';
+
+describe("Proposal guards", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+	const lamports = anchor.web3.LAMPORTS_PER_SOL / 10;
+
+	/** Creates a proposal transferring `lamports` out of the vault, guarded as given. */
+	async function createGuardedProposal(name: string, preGuards: Guard[], postGuards: Guard[]) {
+		const proposal = anchor.web3.Keypair.generate();
+		const compiled = compileActions([
+			anchor.web3.SystemProgram.transfer({
+				fromPubkey: vaultSigner,
+				toPubkey: payerPublicKey,
+				lamports,
+			}),
+		]);
+
+		await program.methods
+			.createProposal({
+				name,
+				accountKeys: compiled.accountKeys,
+				actions: compiled.actions,
+				ephemeralSignerCount: 0,
+				coSigners: [],
+			})
+			.accountsPartial({
+				proposal: proposal.publicKey,
+				proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+				vault: vaultPublicKey,
+				proposer: payerPublicKey,
+			})
+			.signers([payer, proposal])
+			.rpc();
+
+		await program.methods
+			.setProposalGuards({ preGuards, postGuards })
+			.accounts({
+				proposal: proposal.publicKey,
+				vault: vaultPublicKey,
+				proposer: payerPublicKey,
+			})
+			.signers([payer])
+			.rpc();
+
+		return {
+			proposal: proposal.publicKey,
+			remainingAccounts: toChunkRemainingAccounts(program.programId, proposal.publicKey, [
+				compiled,
+			]),
+		};
+	}
+
+	async function executeProposal(
+		proposal: anchor.web3.PublicKey,
+		remainingAccounts: anchor.web3.AccountMeta[],
+	) {
+		return program.methods
+			.executeProposal()
+			.accounts({
+				proposal,
+				caller: payerPublicKey,
+			})
+			.remainingAccounts(remainingAccounts)
+			.signers([payer])
+			.rpc();
+	}
+
+	before(async () => {
+		await createVault(program, payer, vaultFixture, 2 * anchor.web3.LAMPORTS_PER_SOL);
+	});
+
+	describe("flow: createProposal() -> setProposalGuards() -> executeProposal()", () => {
+		it("executes when every guard holds", async () => {
+			const { proposal, remainingAccounts } = await createGuardedProposal(
+				"Guarded transfer",
+				[{ account: vaultSigner, condition: { ownedBy: { owner: anchor.web3.SystemProgram.programId } } }],
+				[
+					{ account: vaultSigner, condition: { maxLamportsDecrease: { maxDecrease: new anchor.BN(lamports) } } },
+					{ account: vaultSigner, condition: { minLamports: { minLamports: new anchor.BN(lamports) } } },
+				],
+			);
+
+			const proposalInfo = await provider.connection.getAccountInfo(proposal);
+			assert.equal(proposalInfo!.data.length, 8 + calculateProposalSize("Guarded transfer", 0, 0, 3));
+
+			await executeProposal(proposal, remainingAccounts);
+
+			const proposalAccount = await program.account.proposal.fetch(proposal);
+			assert(proposalAccount.isExecuted, "Proposal must be executed");
+		});
+
+		it("reverts when a post guard is violated", async () => {
+			const { proposal, remainingAccounts } = await createGuardedProposal(
+				"Over budget transfer",
+				[],
+				[
+					{ account: vaultSigner, condition: { maxLamportsDecrease: { maxDecrease: new anchor.BN(lamports - 1) } } },
+				],
+			);
+
+			try {
+				await executeProposal(proposal, remainingAccounts);
+				assert.fail("Violated post guard must revert the execution");
+			} catch (error) {
+				assertErrorCode(error, "GuardViolated");
+			}
+		});
+
+		it("reverts when a pre guard is violated", async () => {
+			const { proposal, remainingAccounts } = await createGuardedProposal(
+				"Wrong owner transfer",
+				[{ account: vaultSigner, condition: { ownedBy: { owner: TOKEN_PROGRAM_ID } } }],
+				[],
+			);
+
+			try {
+				await executeProposal(proposal, remainingAccounts);
+				assert.fail("Violated pre guard must revert the execution");
+			} catch (error) {
+				assertErrorCode(error, "GuardViolated");
+			}
+		});
+
+		it("rejects relative guards before execution", async () => {
+			try {
+				await createGuardedProposal(
+					"Relative pre guard",
+					[{ account: vaultSigner, condition: { maxLamportsDecrease: { maxDecrease: new anchor.BN(0) } } }],
+					[],
+				);
+				assert.fail("Relative pre guard must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "InvalidGuard");
+			}
+		});
+	});
+});
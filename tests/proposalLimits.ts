@@ -0,0 +1,119 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	assertErrorCode,
+	compileActions,
+	createVault,
+	findProposalActions,
+	newVaultFixture,
+	toRemainingAccounts,
+} from './shared';
+
+describe("Proposal limits", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+
+	describe("flow: createVault() -> setProposalLimits() -> createProposal() / executeProposalDirect()", () => {
+		it("rejects proposals and direct executions beyond the vault limits", async () => {
+			await createVault(program, payer, vaultFixture);
+
+			let vaultAccount = await program.account.vault.fetch(vaultPublicKey);
+			assert(vaultAccount.proposalLimits.maxNameLen === 64, "Vault must start with default limits");
+
+			const setLimitsSignature = await program.methods
+				.setProposalLimits({
+					maxNameLen: 8,
+					maxActions: 1,
+					maxActionDataLen: 16,
+					maxAccountSpecs: 4,
+				})
+				.accounts({
+					owner: payerPublicKey,
+					vault: vaultPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+
+			console.log("Set Proposal Limits Signature: ", setLimitsSignature);
+
+			vaultAccount = await program.account.vault.fetch(vaultPublicKey);
+			assert(vaultAccount.proposalLimits.maxActions === 1, "Max actions does not match");
+
+			const ix = anchor.web3.SystemProgram.transfer({
+				fromPubkey: vaultSigner,
+				toPubkey: payerPublicKey,
+				lamports: 1,
+			});
+			const compiled = compileActions([ix]);
+
+			const proposal = anchor.web3.Keypair.generate();
+			try {
+				await program.methods
+					.createProposal({
+						name: "A proposal name that is too long",
+						accountKeys: compiled.accountKeys,
+						actions: compiled.actions,
+						ephemeralSignerCount: 0,
+						coSigners: [],
+					})
+					.accountsPartial({
+						proposal: proposal.publicKey,
+						proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.signers([payer, proposal])
+					.rpc();
+
+				assert.fail("Long proposal name must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "NameTooLong");
+			}
+
+			const twoActions = compileActions([ix, ix]);
+			try {
+				await program.methods
+					.executeProposalDirect({ nonce: new anchor.BN(0), actions: twoActions.actions })
+					.accounts({
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.remainingAccounts(toRemainingAccounts(twoActions.allAccountKeys, twoActions.actions))
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Too many actions must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "TooManyActions");
+			}
+
+			const largeData = compileActions([{ ...ix, data: Buffer.alloc(17) }]);
+			try {
+				await program.methods
+					.executeProposalDirect({ nonce: new anchor.BN(0), actions: largeData.actions })
+					.accounts({
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.remainingAccounts(toRemainingAccounts(largeData.allAccountKeys, largeData.actions))
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Large action data must be rejected");
+			} catch (error) {
+				assertErrorCode(error, "ActionDataTooLarge");
+			}
+		});
+	});
+});
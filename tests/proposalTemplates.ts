@@ -0,0 +1,173 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+	Action,
+	compileActions,
+	createVault,
+	findProposalActions,
+	newVaultFixture,
+	toChunkRemainingAccounts,
+} from './shared';
+
+describe("Proposal templates", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const feeRecipient = anchor.web3.Keypair.generate().publicKey;
+	const lamports = anchor.web3.LAMPORTS_PER_SOL / 10;
+	const template = anchor.web3.Keypair.generate();
+
+	async function createFundedVault() {
+		const vaultFixture = newVaultFixture(program.programId);
+		await createVault(program, payer, vaultFixture, anchor.web3.LAMPORTS_PER_SOL);
+		return vaultFixture;
+	}
+
+	before(async () => {
+		// A fee sweep paying `lamports` from whichever vault instantiates the template.
+		const action: Action = {
+			programIdIndex: 0,
+			accountSpecs: [
+				{ accountIndex: 1, isSigner: true, isWritable: true, subAccountIndex: null },
+				{ accountIndex: 2, isSigner: false, isWritable: true, subAccountIndex: null },
+			],
+			data: anchor.web3.SystemProgram.transfer({
+				fromPubkey: payerPublicKey,
+				toPubkey: feeRecipient,
+				lamports,
+			}).data,
+			dataBuffer: null,
+			placeholders: [],
+			policy: { atomic: {} },
+		};
+
+		await program.methods
+			.createTemplate({
+				name: "Fee sweep",
+				accountKeys: [
+					{ fixed: { pubkey: anchor.web3.SystemProgram.programId } },
+					{ vaultSigner: {} },
+					{ fixed: { pubkey: feeRecipient } },
+				],
+				actions: [action],
+			})
+			.accounts({
+				template: template.publicKey,
+				authority: payerPublicKey,
+			})
+			.signers([payer, template])
+			.rpc();
+	});
+
+	describe("flow: createTemplate() -> instantiateTemplate() -> executeProposal()", () => {
+		it("instantiates the same template for several vaults", async () => {
+			const vaults = [await createFundedVault(), await createFundedVault()];
+			for (const [index, { vaultPublicKey: vault, vaultSigner }] of vaults.entries()) {
+				const proposal = anchor.web3.Keypair.generate();
+
+				await program.methods
+					.instantiateTemplate({
+						name: "Fee sweep",
+						ephemeralSignerCount: 0,
+						coSigners: [],
+					})
+					.accountsPartial({
+						proposal: proposal.publicKey,
+						proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+						template: template.publicKey,
+						vault,
+						proposer: payerPublicKey,
+					})
+					.signers([payer, proposal])
+					.rpc();
+
+				// The resolved key table matches compiling the sweep for this vault directly.
+				const compiled = compileActions([
+					anchor.web3.SystemProgram.transfer({
+						fromPubkey: vaultSigner,
+						toPubkey: feeRecipient,
+						lamports,
+					}),
+				]);
+
+				await program.methods
+					.executeProposal()
+					.accounts({
+						proposal: proposal.publicKey,
+						caller: payerPublicKey,
+					})
+					.remainingAccounts(
+						toChunkRemainingAccounts(program.programId, proposal.publicKey, [compiled]),
+					)
+					.signers([payer])
+					.rpc();
+
+				assert.equal(await provider.connection.getBalance(feeRecipient), (index + 1) * lamports);
+			}
+		});
+
+		it("rejects ephemeral signers the proposal does not have", async () => {
+			const brokenTemplate = anchor.web3.Keypair.generate();
+			const { vaultPublicKey: vault } = await createFundedVault();
+			const proposal = anchor.web3.Keypair.generate();
+
+			await program.methods
+				.createTemplate({
+					name: "Broken",
+					accountKeys: [
+						{ fixed: { pubkey: anchor.web3.SystemProgram.programId } },
+						{ ephemeralSigner: { index: 0 } },
+					],
+					actions: [],
+				})
+				.accounts({
+					template: brokenTemplate.publicKey,
+					authority: payerPublicKey,
+				})
+				.signers([payer, brokenTemplate])
+				.rpc();
+
+			try {
+				await program.methods
+					.instantiateTemplate({
+						name: "Broken",
+						ephemeralSignerCount: 0,
+						coSigners: [],
+					})
+					.accountsPartial({
+						proposal: proposal.publicKey,
+						proposalActions: findProposalActions(program.programId, proposal.publicKey, 0),
+						template: brokenTemplate.publicKey,
+						vault,
+						proposer: payerPublicKey,
+					})
+					.signers([payer, proposal])
+					.rpc();
+				assert.fail("Unresolvable template key must be rejected");
+			} catch (error) {
+				assert(
+					error instanceof anchor.AnchorError &&
+						error.error.errorCode.code === "InvalidTemplateKey",
+					`Unexpected error: ${error}`,
+				);
+			}
+
+			await program.methods
+				.closeTemplate()
+				.accounts({
+					template: brokenTemplate.publicKey,
+					authority: payerPublicKey,
+				})
+				.signers([payer])
+				.rpc();
+		});
+	});
+});
@@ -1,4 +1,6 @@
 import { BigNumber } from 'bignumber.js';
+import { assert } from 'chai';
+import { createHash } from 'crypto';
 
 import * as anchor from '@coral-xyz/anchor';
 import {
@@ -15,40 +17,347 @@ type TransactionInstruction = {
 	data: Buffer<ArrayBufferLike>;
 };
 
-export function calculateActionsSize(ixs: TransactionInstruction[]) {
-	return ixs
-		.map((ix) => 4 + ix.keys.length * 34 + 4 + ix.data.length + 32)
+type AccountSpec = {
+	accountIndex: number;
+	isSigner: boolean;
+	isWritable: boolean;
+	subAccountIndex: number | null;
+};
+
+export type ExecutionPolicy =
+	| { atomic: {} }
+	| { continueOnError: {} }
+	| { group: { id: number } };
+
+export type AmountSource =
+	| { tokenBalance: { accountIndex: number } }
+	| { vaultLamports: { reserve: anchor.BN } };
+
+export type Placeholder = {
+	/** Offset of the little-endian u64 amount in the instruction data */
+	offset: number;
+	source: AmountSource;
+	/** Share of the source amount, 10_000 for all of it */
+	basisPoints: number;
+};
+
+export type DataBufferRef = {
+	/** Index of the buffer in the account key table */
+	accountIndex: number;
+	dataLen: number;
+	/** SHA-256 hash of the buffer's data */
+	dataHash: number[];
+};
+
+export type Action = {
+	programIdIndex: number;
+	accountSpecs: AccountSpec[];
+	data: Buffer<ArrayBufferLike>;
+	/** Action data buffer holding `data` */
+	dataBuffer: DataBufferRef | null;
+	/** Amounts in `data` filled in when the action is invoked */
+	placeholders: Placeholder[];
+	policy: ExecutionPolicy;
+};
+
+export type CompiledActions = {
+	/** Keys to append to the proposal's account key table */
+	accountKeys: anchor.web3.PublicKey[];
+	/** The full account key table the actions index into */
+	allAccountKeys: anchor.web3.PublicKey[];
+	actions: Action[];
+};
+
+/**
+ * Compiles instructions into actions indexing into a deduplicated account key table,
+ * extending `existingAccountKeys` with any keys it does not hold yet. Actions are atomic
+ * unless `policies` gives another execution policy for the instruction at the same index.
+ */
+export function compileActions(
+	ixs: TransactionInstruction[],
+	existingAccountKeys: anchor.web3.PublicKey[] = [],
+	policies: ExecutionPolicy[] = [],
+): CompiledActions {
+	const allAccountKeys = [...existingAccountKeys];
+	const indexOf = (pubkey: anchor.web3.PublicKey) => {
+		const index = allAccountKeys.findIndex((key) => key.equals(pubkey));
+		if (index !== -1) {
+			return index;
+		}
+		allAccountKeys.push(pubkey);
+		return allAccountKeys.length - 1;
+	};
+
+	const actions = ixs.map((ix, index) => ({
+		programIdIndex: indexOf(ix.programId),
+		accountSpecs: ix.keys.map((key) => ({
+			accountIndex: indexOf(key.pubkey),
+			isSigner: key.isSigner,
+			isWritable: key.isWritable,
+			subAccountIndex: null as number | null,
+		})),
+		data: ix.data,
+		dataBuffer: null as DataBufferRef | null,
+		placeholders: [] as Placeholder[],
+		policy: policies[index] ?? { atomic: {} },
+	}));
+
+	return {
+		accountKeys: allAccountKeys.slice(existingAccountKeys.length),
+		allAccountKeys,
+		actions,
+	};
+}
+
+/** Remaining accounts for an account key table, writable where any action writes. */
+export function toRemainingAccounts(
+	accountKeys: anchor.web3.PublicKey[],
+	actions: Action[],
+	signers: anchor.web3.PublicKey[] = [],
+) {
+	return accountKeys.map((pubkey, index) => ({
+		pubkey,
+		isSigner: signers.some((signer) => signer.equals(pubkey)),
+		isWritable: actions.some((action) =>
+			action.accountSpecs.some((spec) => spec.accountIndex === index && spec.isWritable),
+		),
+	}));
+}
+
+export function calculateActionsSize(actions: Action[]) {
+	return actions
+		.map(
+			(action) =>
+				1 +
+				4 +
+				action.accountSpecs
+					.map((spec) => 4 + (spec.subAccountIndex === null ? 0 : 1))
+					.reduce((acc, curr) => acc + curr, 0) +
+				4 +
+				action.data.length +
+				(action.dataBuffer === null ? 1 : 1 + 1 + 4 + 32) +
+				4 +
+				action.placeholders
+					.map((placeholder) => 2 + 1 + ("tokenBalance" in placeholder.source ? 1 : 8) + 2)
+					.reduce((acc, curr) => acc + curr, 0) +
+				("group" in action.policy ? 2 : 1),
+		)
 		.reduce((acc, curr) => acc + curr, 0);
 }
 
-export function calculateProposalSize(ixs: anchor.web3.TransactionInstruction[]) {
-	const ix_size = calculateActionsSize(ixs);
+/** Space the program reserves per guard: the account and the largest condition. */
+const GUARD_SIZE = 32 + 1 + 32;
+
+/** Exact data size the program allocates for a proposal, excluding the discriminator. */
+export function calculateProposalSize(
+	name: string,
+	ephemeralSignerCount = 0,
+	coSignerCount = 0,
+	guardCount = 0,
+	outflowCap: OutflowCap | null = null,
+	dependencyCount = 0,
+) {
 	// pub struct Proposal {
 	// 	pub vault: Pubkey,
 	// 	pub proposal_stage: ProposalStage,
 	// 	pub created_date: i64,
 	// 	pub expiry_date: i64,
 	// 	pub is_executed: bool,
+	// 	pub ephemeral_signer_bumps: Vec<u8>,
+	// 	pub co_signers: Vec<Pubkey>,
+	// 	pub dependencies: Vec<Pubkey>,
+	// 	pub pre_guards: Vec<Guard>,
+	// 	pub post_guards: Vec<Guard>,
+	// 	pub outflow_cap: Option<OutflowCap>,
 	// 	pub name: String,
-	// 	pub actions: Vec<Action>,
+	// 	pub kind: ProposalKind,
+	// 	pub action_count: u16,
+	// 	pub chunk_count: u16,
+	// 	pub actions_hash: [u8; 32],
+	// 	pub actions_size: u32,
+	// 	pub action_groups: [u8; 32],
+	// 	pub action_outcomes: Vec<ActionOutcome>,
 	// }
 
-	const withExtraSpace =
-		32 + 1 + 8 + 8 + 1 + /** name */ 4 + 60 + /** actions */ 4 + ix_size + /** extra */ 20;
+	return (
+		32 + 1 + 8 + 8 + 1 + /** ephemeral signer bumps */ 4 + ephemeralSignerCount +
+		/** co-signers */ 4 + 32 * coSignerCount +
+		/** dependencies */ 4 + 32 * dependencyCount +
+		/** pre and post guards */ 4 + 4 + GUARD_SIZE * guardCount +
+		/** outflow cap */ 1 + (outflowCap ? calculateOutflowCapSize(outflowCap) : 0) +
+		/** name */ 4 + Buffer.byteLength(name) + /** kind */ 1 + /** action count */ 2 + /** chunk count */ 2 +
+		/** actions hash */ 32 + /** actions size */ 4 + /** action groups */ 32 + /** action outcomes */ 4
+	);
+}
 
-	return withExtraSpace;
+/** Serialized size of an outflow cap stored on a proposal. */
+function calculateOutflowCapSize(outflowCap: OutflowCap) {
+	return (
+		/** max lamports */ 1 + (outflowCap.maxLamports ? 8 : 0) +
+		/** token caps */ 4 + (32 + 8) * outflowCap.tokenCaps.length
+	);
 }
 
-type ProposalStage = { draft: {} } | { completed: {} } | { cancelled: {} } | { failed: {} };
+const PROPOSAL_ACTIONS_PREFIX = anchor.utils.bytes.utf8.encode("aacs_proposal_actions");
 
-type AccountSpec = { pubkey: anchor.web3.PublicKey; isSigner: boolean; isWritable: boolean };
+/** Address of the `ProposalActions` chunk `chunkIndex` of `proposal`. */
+export function findProposalActions(
+	programId: anchor.web3.PublicKey,
+	proposal: anchor.web3.PublicKey,
+	chunkIndex: number,
+) {
+	const index = Buffer.alloc(2);
+	index.writeUInt16LE(chunkIndex);
 
-type Action = {
-	programId: anchor.web3.PublicKey;
-	accountSpecs: AccountSpec[];
-	data: Buffer<ArrayBufferLike>;
+	const [proposalActions] = anchor.web3.PublicKey.findProgramAddressSync(
+		[PROPOSAL_ACTIONS_PREFIX, proposal.toBuffer(), index],
+		programId,
+	);
+	return proposalActions;
+}
+
+/** Remaining accounts for `executeProposal()`: each chunk followed by its account key table. */
+export function toChunkRemainingAccounts(
+	programId: anchor.web3.PublicKey,
+	proposal: anchor.web3.PublicKey,
+	chunks: CompiledActions[],
+	signers: anchor.web3.PublicKey[] = [],
+) {
+	return chunks.flatMap((chunk, chunkIndex) => [
+		{
+			pubkey: findProposalActions(programId, proposal, chunkIndex),
+			isSigner: false,
+			isWritable: false,
+		},
+		...toRemainingAccounts(chunk.allAccountKeys, chunk.actions, signers),
+	]);
+}
+
+/**
+ * Digest a committed proposal stores for `actions` over `accountKeys`: the chunk hash of
+ * a single `ProposalActions` chunk holding them.
+ */
+export function hashCommittedActions(
+	program: anchor.Program<any>,
+	accountKeys: anchor.web3.PublicKey[],
+	actions: Action[],
+): number[] {
+	return [
+		...createHash("sha256")
+			.update(Buffer.alloc(32))
+			.update(Buffer.concat(accountKeys.map((pubkey) => pubkey.toBuffer())))
+			.update(Buffer.concat(actions.map((action) => program.coder.types.encode("action", action))))
+			.digest(),
+	];
+}
+
+/** Addresses a lookup table extension can carry within one transaction. */
+const LOOKUP_TABLE_EXTEND_BATCH = 20;
+
+/**
+ * Creates an address lookup table holding `addresses`, extending it in batches that fit a
+ * transaction, and waits until the table can be used.
+ */
+export async function createLookupTable(
+	connection: anchor.web3.Connection,
+	payer: anchor.web3.Keypair,
+	addresses: anchor.web3.PublicKey[],
+) {
+	const uniqueAddresses = addresses.filter(
+		(address, index) => addresses.findIndex((other) => other.equals(address)) === index,
+	);
+
+	const [createIx, lookupTable] = anchor.web3.AddressLookupTableProgram.createLookupTable({
+		authority: payer.publicKey,
+		payer: payer.publicKey,
+		recentSlot: await connection.getSlot("finalized"),
+	});
+	await sendV0Transaction(connection, payer, [createIx]);
+
+	let lastExtendedSlot = 0;
+	for (let start = 0; start < uniqueAddresses.length; start += LOOKUP_TABLE_EXTEND_BATCH) {
+		const extendIx = anchor.web3.AddressLookupTableProgram.extendLookupTable({
+			lookupTable,
+			authority: payer.publicKey,
+			payer: payer.publicKey,
+			addresses: uniqueAddresses.slice(start, start + LOOKUP_TABLE_EXTEND_BATCH),
+		});
+		await sendV0Transaction(connection, payer, [extendIx]);
+		lastExtendedSlot = await connection.getSlot();
+	}
+
+	// Addresses can only be looked up from the slot after they were added.
+	while ((await connection.getSlot()) <= lastExtendedSlot) {
+		await sleep(100);
+	}
+
+	return (await connection.getAddressLookupTable(lookupTable)).value!;
+}
+
+/** Creates a lookup table holding every account `executeProposal()` passes for `chunks`. */
+export async function createProposalLookupTable(
+	connection: anchor.web3.Connection,
+	payer: anchor.web3.Keypair,
+	programId: anchor.web3.PublicKey,
+	proposal: anchor.web3.PublicKey,
+	chunks: CompiledActions[],
+) {
+	return createLookupTable(
+		connection,
+		payer,
+		toChunkRemainingAccounts(programId, proposal, chunks).map((account) => account.pubkey),
+	);
+}
+
+/** Sends `ixs` in a v0 transaction that resolves accounts through `lookupTables`. */
+export async function sendV0Transaction(
+	connection: anchor.web3.Connection,
+	payer: anchor.web3.Keypair,
+	ixs: anchor.web3.TransactionInstruction[],
+	lookupTables: anchor.web3.AddressLookupTableAccount[] = [],
+	signers: anchor.web3.Keypair[] = [],
+) {
+	const { blockhash, lastValidBlockHeight } = await connection.getLatestBlockhash();
+	const message = new anchor.web3.TransactionMessage({
+		payerKey: payer.publicKey,
+		recentBlockhash: blockhash,
+		instructions: ixs,
+	}).compileToV0Message(lookupTables);
+
+	const transaction = new anchor.web3.VersionedTransaction(message);
+	transaction.sign([payer, ...signers]);
+
+	const signature = await connection.sendTransaction(transaction);
+	const { value } = await connection.confirmTransaction(
+		{ signature, blockhash, lastValidBlockHeight },
+		"confirmed",
+	);
+	if (value.err) {
+		throw new Error(`Transaction ${signature} failed: ${JSON.stringify(value.err)}`);
+	}
+
+	return signature;
+}
+
+export type GuardCondition =
+	| { minLamports: { minLamports: anchor.BN } }
+	| { minTokenAmount: { minAmount: anchor.BN } }
+	| { ownedBy: { owner: anchor.web3.PublicKey } }
+	| { maxLamportsDecrease: { maxDecrease: anchor.BN } }
+	| { maxTokenDecrease: { maxDecrease: anchor.BN } };
+
+export type Guard = {
+	account: anchor.web3.PublicKey;
+	condition: GuardCondition;
+};
+
+export type OutflowCap = {
+	maxLamports: anchor.BN | null;
+	tokenCaps: { mint: anchor.web3.PublicKey; maxAmount: anchor.BN }[];
 };
 
+type ProposalStage = { draft: {} } | { completed: {} } | { cancelled: {} } | { failed: {} };
+
 type Proposal = {
 	vault: anchor.web3.PublicKey;
 	name: string;
@@ -56,7 +365,19 @@ type Proposal = {
 	createdDate: anchor.BN;
 	expiryDate: anchor.BN;
 	isExecuted: boolean;
-	actions: Action[];
+	ephemeralSignerBumps: number[];
+	coSigners: anchor.web3.PublicKey[];
+	dependencies: anchor.web3.PublicKey[];
+	preGuards: Guard[];
+	postGuards: Guard[];
+	outflowCap: OutflowCap | null;
+	kind: { stored: {} } | { committed: {} };
+	actionCount: number;
+	chunkCount: number;
+	actionsHash: number[];
+	actionsSize: number;
+	actionGroups: number[];
+	actionOutcomes: ({ executed: {} } | { skipped: {} })[];
 };
 
 export function printProposalAccount(proposalAccount: Proposal) {
@@ -68,15 +389,8 @@ export function printProposalAccount(proposalAccount: Proposal) {
 				createdDate: unixTimestampToDate(proposalAccount.createdDate.toNumber()),
 				expiryDate: unixTimestampToDate(proposalAccount.expiryDate.toNumber()),
 				vault: proposalAccount.vault.toString(),
-				actions: proposalAccount.actions.map((action) => ({
-					accountSpecs: action.accountSpecs.map((spec) => ({
-						...spec,
-						pubkey: spec.pubkey.toString(),
-					})),
-
-					data: action.data.toString("base64"),
-					programId: action.programId.toString(),
-				})),
+				coSigners: proposalAccount.coSigners.map((coSigner) => coSigner.toString()),
+				actionsHash: Buffer.from(proposalAccount.actionsHash).toString("hex"),
 			},
 			null,
 			2,
@@ -92,6 +406,8 @@ type Vault = {
 	owner: anchor.web3.PublicKey;
 	createdDate: anchor.BN;
 	signerBump: number;
+	directExecutionNonce: anchor.BN;
+	directExecutionDisabled: boolean;
 };
 
 export function printVaultAccount(vaultAccount: Vault) {
@@ -157,3 +473,54 @@ export async function createNewMint(
 
 	return mint;
 }
+
+export function assertErrorCode(error: unknown, code: string) {
+	assert(
+		error instanceof anchor.AnchorError && error.error.errorCode.code === code,
+		`Unexpected error: ${error}`,
+	);
+}
+
+export const VAULT_SIGNER_PREFIX = anchor.utils.bytes.utf8.encode("aacs_vault_signer");
+
+export type VaultFixture = {
+	vault: anchor.web3.Keypair;
+	vaultPublicKey: anchor.web3.PublicKey;
+	vaultSigner: anchor.web3.PublicKey;
+	vaultBump: number;
+};
+
+export function newVaultFixture(programId: anchor.web3.PublicKey): VaultFixture {
+	const vault = anchor.web3.Keypair.generate();
+	const [vaultSigner, vaultBump] = anchor.web3.PublicKey.findProgramAddressSync(
+		[VAULT_SIGNER_PREFIX, vault.publicKey.toBuffer()],
+		programId,
+	);
+	return { vault, vaultPublicKey: vault.publicKey, vaultSigner, vaultBump };
+}
+
+/** Creates the fixture's vault owned by `owner` and funds its vault signer with `lamports`. */
+export async function createVault(
+	program: anchor.Program<any>,
+	owner: anchor.web3.Keypair,
+	fixture: VaultFixture,
+	lamports = 0,
+) {
+	const fund = lamports > 0
+		? [
+			anchor.web3.SystemProgram.transfer({
+				fromPubkey: owner.publicKey,
+				toPubkey: fixture.vaultSigner,
+				lamports,
+			}),
+		]
+		: [];
+	return program.methods
+		.createVault({ owner: owner.publicKey, signerBump: fixture.vaultBump })
+		.accounts({
+			vault: fixture.vaultPublicKey,
+		})
+		.postInstructions(fund)
+		.signers([fixture.vault, owner])
+		.rpc();
+}
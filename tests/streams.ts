@@ -0,0 +1,371 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  TOKEN_PROGRAM_ID,
+  transfer,
+} from '@solana/spl-token';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import {
+  createNewMint,
+  createVault,
+  getBlockTime,
+  newVaultFixture,
+  sleep,
+} from './shared';
+
+describe("Stream flow", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const STREAM_PREFIX = anchor.utils.bytes.utf8.encode("aacs_stream");
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const recipient = anchor.web3.Keypair.generate();
+	const vaultFixture = newVaultFixture(program.programId);
+	const { vaultPublicKey, vaultSigner } = vaultFixture;
+
+	function findStreamAddress(streamId: number) {
+		return anchor.web3.PublicKey.findProgramAddressSync(
+			[STREAM_PREFIX, vaultPublicKey.toBuffer(), new anchor.BN(streamId).toArrayLike(Buffer, "le", 8)],
+			program.programId,
+		)[0];
+	}
+
+	describe("flow: createVault() -> createStream() -> withdrawFromStream() -> cancelStream()", () => {
+		it("works as expected", async () => {
+			const createVaultSignature = await createVault(
+				program,
+				payer,
+				vaultFixture,
+				10 * anchor.web3.LAMPORTS_PER_SOL,
+			);
+			await provider.sendAndConfirm!(
+				new anchor.web3.Transaction().add(
+					anchor.web3.SystemProgram.transfer({
+						fromPubkey: payerPublicKey,
+						toPubkey: recipient.publicKey,
+						lamports: anchor.web3.LAMPORTS_PER_SOL,
+					}),
+				),
+			);
+
+			console.log("Create Vault Signature: ", createVaultSignature);
+
+			const totalAmount = 4 * anchor.web3.LAMPORTS_PER_SOL;
+			const startDate = await getBlockTime(provider.connection);
+			const endDate = startDate + 8;
+			const stream = findStreamAddress(0);
+			const vaultSignerBalanceA = await provider.connection.getBalance(vaultSigner);
+
+			const createStreamSignature = await program.methods
+				.createStream({
+					recipient: recipient.publicKey,
+					totalAmount: new anchor.BN(totalAmount),
+					startDate: new anchor.BN(startDate),
+					cliffDate: null,
+					endDate: new anchor.BN(endDate),
+				})
+				.accountsPartial({
+					owner: payerPublicKey,
+					vault: vaultPublicKey,
+					stream,
+					tokenMint: null,
+					vaultSignerTokenAccount: null,
+					streamTokenAccount: null,
+					tokenProgram: null,
+					associatedTokenProgram: null,
+				})
+				.signers([payer])
+				.rpc();
+
+			console.log("Create Stream Signature: ", createStreamSignature);
+
+			await sleep(1000);
+			const vaultSignerBalanceB = await provider.connection.getBalance(vaultSigner);
+			assert(
+				vaultSignerBalanceA - vaultSignerBalanceB === totalAmount,
+				"The whole stream amount must move out of the vault signer",
+			);
+			assert(
+				(await provider.connection.getBalance(stream)) >= totalAmount,
+				"The stream must escrow the whole stream amount",
+			);
+
+			// The owner can no longer spend what the stream promised to the recipient.
+			await program.methods
+				.withdrawSol({ amount: new anchor.BN(vaultSignerBalanceB) })
+				.accountsPartial({
+					vault: vaultPublicKey,
+					withdrawer: payerPublicKey,
+					vaultSigner,
+				})
+				.signers([payer])
+				.rpc();
+
+			let streamAccount = await program.account.stream.fetch(stream);
+			assert(streamAccount.recipient.equals(recipient.publicKey), "Stream recipient does not match");
+			assert(streamAccount.mint === null, "SOL stream must not have a mint");
+			assert(streamAccount.withdrawnAmount.eqn(0), "Nothing must be withdrawn on creation");
+
+			const vaultAccount = await program.account.vault.fetch(vaultPublicKey);
+			assert(vaultAccount.streamCount.eqn(1), "Vault stream count must be incremented");
+
+			await sleep(3000);
+
+			const recipientBalanceA = await provider.connection.getBalance(recipient.publicKey);
+			const withdrawSignature = await program.methods
+				.withdrawFromStream()
+				.accountsPartial({
+					recipient: recipient.publicKey,
+					vault: vaultPublicKey,
+					stream,
+					tokenMint: null,
+					streamTokenAccount: null,
+					recipientTokenAccount: null,
+					tokenProgram: null,
+				})
+				.signers([recipient])
+				.rpc();
+
+			console.log("Withdraw From Stream Signature: ", withdrawSignature);
+
+			await sleep(1000);
+			const recipientBalanceB = await provider.connection.getBalance(recipient.publicKey);
+			streamAccount = await program.account.stream.fetch(stream);
+
+			assert(streamAccount.withdrawnAmount.gtn(0), "Withdrawn amount must be recorded");
+			assert(streamAccount.withdrawnAmount.ltn(totalAmount), "Stream must not be fully vested yet");
+			assert(
+				recipientBalanceB - recipientBalanceA === streamAccount.withdrawnAmount.toNumber() - 5000,
+				"Recipient balance must grow by the withdrawn amount minus fees",
+			);
+
+			const vaultSignerBalanceC = await provider.connection.getBalance(vaultSigner);
+			const cancelSignature = await program.methods
+				.cancelStream()
+				.accountsPartial({
+					owner: payerPublicKey,
+					vault: vaultPublicKey,
+					stream,
+					recipient: recipient.publicKey,
+					tokenMint: null,
+					vaultSignerTokenAccount: null,
+					streamTokenAccount: null,
+					recipientTokenAccount: null,
+					tokenProgram: null,
+					associatedTokenProgram: null,
+				})
+				.signers([payer])
+				.rpc();
+
+			console.log("Cancel Stream Signature: ", cancelSignature);
+
+			await sleep(1000);
+			const vaultSignerBalanceD = await provider.connection.getBalance(vaultSigner);
+			const recipientBalanceC = await provider.connection.getBalance(recipient.publicKey);
+
+			assert(
+				vaultSignerBalanceD - vaultSignerBalanceC + recipientBalanceC - recipientBalanceB ===
+					totalAmount - streamAccount.withdrawnAmount.toNumber(),
+				"Cancel must split the escrow between the recipient and the vault signer",
+			);
+			assert(
+				vaultSignerBalanceD > vaultSignerBalanceC,
+				"The unvested remainder must return to the vault signer",
+			);
+			assert(
+				(await program.account.stream.fetchNullable(stream)) === null,
+				"Stream must be closed after cancel",
+			);
+		});
+	});
+
+	describe("flow: createStream() -> withdrawFromStream() -> cancelStream() with tokens", () => {
+		const mintDecimals = 6;
+		let mint: anchor.web3.PublicKey;
+		let vaultSignerTokenAccount: anchor.web3.PublicKey;
+
+		async function createTokenStream(
+			streamRecipient: anchor.web3.PublicKey,
+			totalAmount: number,
+			startDate: number,
+			endDate: number,
+		) {
+			const vaultAccount = await program.account.vault.fetch(vaultPublicKey);
+			const stream = findStreamAddress(vaultAccount.streamCount.toNumber());
+			const streamTokenAccount = getAssociatedTokenAddressSync(mint, stream, true);
+
+			await program.methods
+				.createStream({
+					recipient: streamRecipient,
+					totalAmount: new anchor.BN(totalAmount),
+					startDate: new anchor.BN(startDate),
+					cliffDate: null,
+					endDate: new anchor.BN(endDate),
+				})
+				.accountsPartial({
+					owner: payerPublicKey,
+					vault: vaultPublicKey,
+					stream,
+					tokenMint: mint,
+					vaultSignerTokenAccount,
+					streamTokenAccount,
+					tokenProgram: TOKEN_PROGRAM_ID,
+					associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+				})
+				.signers([payer])
+				.rpc();
+
+			return { stream, streamTokenAccount };
+		}
+
+		async function cancelTokenStream(
+			stream: anchor.web3.PublicKey,
+			streamTokenAccount: anchor.web3.PublicKey,
+			streamRecipient: anchor.web3.PublicKey,
+		) {
+			await program.methods
+				.cancelStream()
+				.accountsPartial({
+					owner: payerPublicKey,
+					vault: vaultPublicKey,
+					stream,
+					recipient: streamRecipient,
+					tokenMint: mint,
+					vaultSignerTokenAccount,
+					streamTokenAccount,
+					recipientTokenAccount: getAssociatedTokenAddressSync(mint, streamRecipient, true),
+					tokenProgram: TOKEN_PROGRAM_ID,
+					associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+				})
+				.signers([payer])
+				.rpc();
+		}
+
+		async function tokenBalance(tokenAccount: anchor.web3.PublicKey) {
+			return Number((await getAccount(provider.connection, tokenAccount)).amount);
+		}
+
+		before(async () => {
+			mint = await createNewMint(
+				provider.connection,
+				payer,
+				anchor.web3.Keypair.generate(),
+				mintDecimals,
+			);
+			vaultSignerTokenAccount = (
+				await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, vaultSigner, true)
+			).address;
+
+			await transfer(
+				provider.connection,
+				payer,
+				getAssociatedTokenAddressSync(mint, payerPublicKey),
+				vaultSignerTokenAccount,
+				payer,
+				1000 * 10 ** mintDecimals,
+			);
+		});
+
+		it("escrows the tokens and splits them on cancel", async () => {
+			const totalAmount = 400 * 10 ** mintDecimals;
+			const startDate = await getBlockTime(provider.connection);
+			const vaultSignerTokensA = await tokenBalance(vaultSignerTokenAccount);
+
+			const { stream, streamTokenAccount } = await createTokenStream(
+				recipient.publicKey,
+				totalAmount,
+				startDate,
+				startDate + 8,
+			);
+
+			assert.equal(vaultSignerTokensA - (await tokenBalance(vaultSignerTokenAccount)), totalAmount);
+			assert.equal(await tokenBalance(streamTokenAccount), totalAmount);
+
+			await sleep(3000);
+
+			const recipientTokenAccount = (
+				await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, recipient.publicKey)
+			).address;
+
+			await program.methods
+				.withdrawFromStream()
+				.accountsPartial({
+					recipient: recipient.publicKey,
+					vault: vaultPublicKey,
+					stream,
+					tokenMint: mint,
+					streamTokenAccount,
+					recipientTokenAccount,
+					tokenProgram: TOKEN_PROGRAM_ID,
+				})
+				.signers([recipient])
+				.rpc();
+
+			const streamAccount = await program.account.stream.fetch(stream);
+			const withdrawnAmount = streamAccount.withdrawnAmount.toNumber();
+			assert.isAbove(withdrawnAmount, 0);
+			assert.isBelow(withdrawnAmount, totalAmount);
+			assert.equal(await tokenBalance(recipientTokenAccount), withdrawnAmount);
+			assert.equal(await tokenBalance(streamTokenAccount), totalAmount - withdrawnAmount);
+
+			const vaultSignerTokensB = await tokenBalance(vaultSignerTokenAccount);
+			await cancelTokenStream(stream, streamTokenAccount, recipient.publicKey);
+
+			assert.equal(
+				(await tokenBalance(vaultSignerTokenAccount)) -
+					vaultSignerTokensB +
+					(await tokenBalance(recipientTokenAccount)) -
+					withdrawnAmount,
+				totalAmount - withdrawnAmount,
+			);
+			assert.isNull(await provider.connection.getAccountInfo(streamTokenAccount));
+			assert.isNull(await program.account.stream.fetchNullable(stream));
+		});
+
+		it("creates the token account of a PDA recipient on cancel", async () => {
+			const [pdaRecipient] = anchor.web3.PublicKey.findProgramAddressSync(
+				[anchor.utils.bytes.utf8.encode("stream_recipient")],
+				program.programId,
+			);
+			const recipientTokenAccount = getAssociatedTokenAddressSync(mint, pdaRecipient, true);
+			const totalAmount = 100 * 10 ** mintDecimals;
+			const now = await getBlockTime(provider.connection);
+
+			const { stream, streamTokenAccount } = await createTokenStream(
+				pdaRecipient,
+				totalAmount,
+				now - 100,
+				now + 100,
+			);
+
+			assert.isNull(await provider.connection.getAccountInfo(recipientTokenAccount));
+
+			const vaultSignerLamportsA = await provider.connection.getBalance(vaultSigner);
+			const vaultSignerTokensA = await tokenBalance(vaultSignerTokenAccount);
+			await cancelTokenStream(stream, streamTokenAccount, pdaRecipient);
+
+			const recipientTokens = await tokenBalance(recipientTokenAccount);
+			assert.isAbove(recipientTokens, 0);
+			assert.equal(
+				(await tokenBalance(vaultSignerTokenAccount)) - vaultSignerTokensA + recipientTokens,
+				totalAmount,
+			);
+			assert.isBelow(
+				await provider.connection.getBalance(vaultSigner),
+				vaultSignerLamportsA,
+				"The vault signer must pay for the recipient's token account",
+			);
+		});
+	});
+});
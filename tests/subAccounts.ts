@@ -0,0 +1,167 @@
+import { assert } from 'chai';
+
+import * as anchor from '@coral-xyz/anchor';
+import { Program } from '@coral-xyz/anchor';
+
+import { AacsVault } from '../target/types/aacs_vault';
+import { compileActions, sleep, toRemainingAccounts } from './shared';
+
+describe("Vault sub-account flow", () => {
+	// Configure the client to use the local cluster.
+	anchor.setProvider(anchor.AnchorProvider.env());
+	const provider = anchor.getProvider();
+	const program = anchor.workspace.aacsVault as Program<AacsVault>;
+
+	const VAULT_SIGNER_PREFIX = anchor.utils.bytes.utf8.encode("aacs_vault_signer");
+	const SUB_ACCOUNT_PREFIX = anchor.utils.bytes.utf8.encode("aacs_vault_sub_account");
+
+	const payer = provider.wallet!.payer!;
+	const payerPublicKey = payer.publicKey;
+	const vault = anchor.web3.Keypair.generate();
+	const vaultPublicKey = vault.publicKey;
+	const [, vaultBump] = anchor.web3.PublicKey.findProgramAddressSync(
+		[VAULT_SIGNER_PREFIX, vaultPublicKey.toBuffer()],
+		program.programId,
+	);
+
+	const subAccountIndex = 1;
+	const [subAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+		[SUB_ACCOUNT_PREFIX, vaultPublicKey.toBuffer(), Buffer.from([subAccountIndex])],
+		program.programId,
+	);
+	const [subAccountSigner] = anchor.web3.PublicKey.findProgramAddressSync(
+		[VAULT_SIGNER_PREFIX, vaultPublicKey.toBuffer(), Buffer.from([subAccountIndex])],
+		program.programId,
+	);
+
+	describe("flow: createVault() -> openSubAccount() -> depositSol() -> executeProposalDirect()", () => {
+		it("works as expected", async () => {
+			const createVaultSignature = await program.methods
+				.createVault({ owner: payerPublicKey, signerBump: vaultBump })
+				.accounts({
+					vault: vaultPublicKey,
+				})
+				.signers([vault, payer])
+				.rpc();
+
+			console.log("Create Vault Signature: ", createVaultSignature);
+
+			const openSubAccountSignature = await program.methods
+				.openSubAccount({ index: subAccountIndex })
+				.accountsPartial({
+					owner: payerPublicKey,
+					vault: vaultPublicKey,
+					subAccount,
+				})
+				.signers([payer])
+				.rpc();
+
+			console.log("Open Sub Account Signature: ", openSubAccountSignature);
+
+			const subAccountAccount = await program.account.vaultSubAccount.fetch(subAccount);
+			assert(subAccountAccount.index === subAccountIndex, "Sub account index does not match");
+
+			const depositAmount = 5 * anchor.web3.LAMPORTS_PER_SOL;
+			const depositSolSignature = await program.methods
+				.depositSol({ amount: new anchor.BN(depositAmount) })
+				.accountsPartial({
+					depositor: payerPublicKey,
+					vault: vaultPublicKey,
+					subAccount,
+					vaultSigner: subAccountSigner,
+				})
+				.signers([payer])
+				.rpc();
+
+			console.log("Deposit Signature: ", depositSolSignature);
+
+			await sleep(1000);
+			const subAccountBalanceA = await provider.connection.getBalance(subAccountSigner);
+			assert(subAccountBalanceA === depositAmount, "Sub account balance after deposit does not match");
+
+			const withdrawAmount = 1 * anchor.web3.LAMPORTS_PER_SOL;
+			const ix = anchor.web3.SystemProgram.transfer({
+				fromPubkey: subAccountSigner,
+				toPubkey: payerPublicKey,
+				lamports: withdrawAmount,
+			});
+
+			const compiled = compileActions([ix]);
+			for (const spec of compiled.actions[0].accountSpecs) {
+				if (compiled.allAccountKeys[spec.accountIndex].equals(subAccountSigner)) {
+					spec.subAccountIndex = subAccountIndex;
+				}
+			}
+
+			const executionSignature = await program.methods
+				.executeProposalDirect({ nonce: new anchor.BN(0), actions: compiled.actions })
+				.accounts({
+					vault: vaultPublicKey,
+					proposer: payerPublicKey,
+				})
+				.remainingAccounts([
+					...toRemainingAccounts(compiled.allAccountKeys, compiled.actions),
+					{ pubkey: subAccount, isSigner: false, isWritable: false },
+				])
+				.signers([payer])
+				.rpc();
+
+			console.log("Execution signature:", executionSignature);
+
+			await sleep(1000);
+			const subAccountBalanceB = await provider.connection.getBalance(subAccountSigner);
+			assert(
+				subAccountBalanceA - subAccountBalanceB === withdrawAmount,
+				"Sub account balance after execution does not match",
+			);
+		});
+
+		it("does not sign for sub-accounts that were not opened", async () => {
+			const unopenedIndex = 2;
+			const [unopenedSubAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+				[SUB_ACCOUNT_PREFIX, vaultPublicKey.toBuffer(), Buffer.from([unopenedIndex])],
+				program.programId,
+			);
+			const [unopenedSigner] = anchor.web3.PublicKey.findProgramAddressSync(
+				[VAULT_SIGNER_PREFIX, vaultPublicKey.toBuffer(), Buffer.from([unopenedIndex])],
+				program.programId,
+			);
+
+			const compiled = compileActions([
+				anchor.web3.SystemProgram.transfer({
+					fromPubkey: unopenedSigner,
+					toPubkey: payerPublicKey,
+					lamports: 0,
+				}),
+			]);
+			for (const spec of compiled.actions[0].accountSpecs) {
+				if (compiled.allAccountKeys[spec.accountIndex].equals(unopenedSigner)) {
+					spec.subAccountIndex = unopenedIndex;
+				}
+			}
+
+			try {
+				await program.methods
+					.executeProposalDirect({ nonce: new anchor.BN(1), actions: compiled.actions })
+					.accounts({
+						vault: vaultPublicKey,
+						proposer: payerPublicKey,
+					})
+					.remainingAccounts([
+						...toRemainingAccounts(compiled.allAccountKeys, compiled.actions),
+						{ pubkey: unopenedSubAccount, isSigner: false, isWritable: false },
+					])
+					.signers([payer])
+					.rpc();
+
+				assert.fail("Unopened sub-account must not be signed for");
+			} catch (error) {
+				assert(
+					error instanceof anchor.AnchorError &&
+						error.error.errorCode.code === "SubAccountNotOpened",
+					`Unexpected error: ${error}`,
+				);
+			}
+		});
+	});
+});